@@ -0,0 +1,108 @@
+//! Command line options for trying things out without clicking through the menus:
+//!
+//! - `--level <name|number>` starts on a level, counting from 1 if given a number
+//! - `--pack <id>` picks the pack `--level` is looked up in, otherwise the first one
+//! - `--windowed <width>x<height>` sets the window size
+//! - `--vsync` waits for the display's refresh
+//! - `--mute` starts muted
+//! - `--debug-physics` draws colliders
+//! - `--log-level <error|warn|info|debug|trace>` prints more or fewer log messages
+//!
+//! Options taking a value also accept `--option=value`. `--replay=` and `--autosplitter` are left
+//! to the plugins that read their own flags, and anything else is an error.
+
+use bevy::math::UVec2;
+use thiserror::Error;
+
+use crate::{
+    autosplitter::AUTOSPLITTER_FLAG,
+    replay::REPLAY_FLAG,
+    state::{LevelSelector, StartingLevel},
+    GamePlugin,
+};
+
+#[derive(Debug, Error)]
+pub enum ArgsError {
+    #[error("unknown option {0}")]
+    UnknownOption(String),
+    #[error("{0} needs a value")]
+    MissingValue(String),
+    #[error("{0} doesn't take a value")]
+    UnexpectedValue(String),
+    #[error("invalid window size {0:?}, expected <width>x<height>")]
+    WindowSize(String),
+    #[error("invalid log level {0:?}, expected error, warn, info, debug or trace")]
+    LogLevel(String),
+    #[error("invalid autosplitter address {0:?}, expected <ip>:<port>")]
+    AutosplitterAddress(String),
+}
+
+impl GamePlugin {
+    /// Configures the game from command line `args`, including the program name
+    pub fn from_args(args: impl Iterator<Item = String>) -> Result<Self, ArgsError> {
+        let mut game = Self::default();
+        let mut pack = None;
+        let mut level = None;
+
+        let mut args = args.skip(1);
+        while let Some(arg) = args.next() {
+            let (option, value) = match arg.split_once('=') {
+                Some((option, value)) => (option, Some(value.to_string())),
+                None => (arg.as_str(), None),
+            };
+            let takes_value = matches!(option, "--level" | "--pack" | "--windowed" | "--log-level");
+            if !takes_value && !is_flag(option) {
+                if option == AUTOSPLITTER_FLAG || arg.starts_with(REPLAY_FLAG) {
+                    continue;
+                }
+                return Err(ArgsError::UnknownOption(arg));
+            }
+            let value = match (takes_value, value) {
+                (true, Some(value)) => value,
+                (true, None) => args
+                    .next()
+                    .ok_or_else(|| ArgsError::MissingValue(option.into()))?,
+                (false, None) => String::new(),
+                (false, Some(_)) => return Err(ArgsError::UnexpectedValue(option.into())),
+            };
+
+            match option {
+                "--level" => level = Some(LevelSelector::from(value.as_str())),
+                "--pack" => pack = Some(value),
+                "--windowed" => game.window_size = Some(parse_window_size(&value)?),
+                "--log-level" => {
+                    game.log_level = value.parse().map_err(|_| ArgsError::LogLevel(value))?
+                }
+                "--vsync" => game.vsync = true,
+                "--mute" => game.muted = true,
+                "--debug-physics" => game.debug_physics = true,
+                _ => {}
+            }
+        }
+
+        if pack.is_some() || level.is_some() {
+            game.starting_level = Some(StartingLevel {
+                pack,
+                level: level.unwrap_or(LevelSelector::Number(1)),
+            });
+        }
+        Ok(game)
+    }
+}
+
+/// Whether `option` is one of the options without a value
+fn is_flag(option: &str) -> bool {
+    matches!(option, "--vsync" | "--mute" | "--debug-physics")
+}
+
+/// Parses `<width>x<height>`
+fn parse_window_size(value: &str) -> Result<UVec2, ArgsError> {
+    let error = || ArgsError::WindowSize(value.into());
+    let (width, height) = value.split_once('x').ok_or_else(error)?;
+    let width = width.parse().map_err(|_| error())?;
+    let height = height.parse().map_err(|_| error())?;
+    if width == 0 || height == 0 {
+        return Err(error());
+    }
+    Ok(UVec2::new(width, height))
+}
@@ -0,0 +1,260 @@
+//! Drives LiveSplit through LiveSplit Server, so splits happen without memory-reading autosplitters.
+//! Start LiveSplit Server (Control > Start TCP Server in LiveSplit) and launch the game with
+//! `--autosplitter`, or `--autosplitter=<ip>:<port>` if it listens somewhere other than the default.
+//! The game connects to it as a client, and keeps trying to reconnect while it isn't running.
+
+use std::{
+    io::{self, Write},
+    net::{SocketAddr, TcpStream},
+    time::Duration,
+};
+
+use bevy::prelude::*;
+
+use crate::{
+    args::ArgsError,
+    menus::{DeathCount, SpeedrunTimer},
+    player::LevelCompletedEvent,
+    splits::format_lss_time,
+    state::LevelLoadingState,
+};
+
+/// Command line flag that turns the autosplitter on, optionally followed by `=<address>`
+pub(crate) const AUTOSPLITTER_FLAG: &str = "--autosplitter";
+
+/// Address LiveSplit Server listens on by default
+const DEFAULT_LIVESPLIT_ADDRESS: &str = "127.0.0.1:16834";
+
+/// How long to wait between attempts to connect to LiveSplit Server
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(2);
+/// How long a single attempt to connect may take, kept short since it holds up the frame
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// Tells LiveSplit Server what the game is doing, using the commands of its text protocol
+pub struct AutosplitterPlugin {
+    /// Address of LiveSplit Server. Nothing is sent if this is `None`.
+    pub address: Option<SocketAddr>,
+}
+
+impl AutosplitterPlugin {
+    /// Reads the address from the `--autosplitter[=<address>]` flag in `args`
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Result<Self, ArgsError> {
+        let address = args.find_map(|arg| {
+            let address = arg.strip_prefix(AUTOSPLITTER_FLAG)?;
+            match address.strip_prefix('=') {
+                Some(address) => Some(address.to_string()),
+                None if address.is_empty() => Some(DEFAULT_LIVESPLIT_ADDRESS.to_string()),
+                None => None,
+            }
+        });
+        let address = address
+            .map(|address| {
+                address
+                    .parse()
+                    .map_err(|_| ArgsError::AutosplitterAddress(address))
+            })
+            .transpose()?;
+        Ok(Self { address })
+    }
+}
+
+impl Plugin for AutosplitterPlugin {
+    fn build(&self, app: &mut App) {
+        let Some(address) = self.address else {
+            return;
+        };
+        let mut reconnect = Timer::new(RECONNECT_INTERVAL, TimerMode::Once);
+        // the first attempt is made right away
+        reconnect.tick(RECONNECT_INTERVAL);
+        app.insert_resource(LiveSplitConnection {
+            address,
+            stream: None,
+            reconnect,
+        })
+        .add_event::<LevelCompletedEvent>()
+        .add_event::<AutosplitterEvent>()
+        .add_systems(
+            Update,
+            (
+                connect_to_livesplit,
+                send_state_transition_events,
+                send_level_completed_events,
+                send_death_events,
+                send_game_time_events,
+            ),
+        )
+        .add_systems(PostUpdate, write_autosplitter_events);
+    }
+}
+
+/// The connection to LiveSplit Server, if it's running
+#[derive(Resource)]
+pub struct LiveSplitConnection {
+    address: SocketAddr,
+    stream: Option<TcpStream>,
+    reconnect: Timer,
+}
+
+/// Something that happened in the game that split tools should know about. Times are the in-game
+/// time of the run in seconds.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub enum AutosplitterEvent {
+    /// A new run was started from the main menu
+    Start,
+    /// The player reached the goal of a level
+    Split(f32),
+    /// The run was abandoned
+    Reset,
+    /// The player died. LiveSplit has no notion of deaths, so this only resyncs the game time.
+    Death(f32),
+    /// The in-game timer stopped, e.g. because a level is loading
+    Pause(f32),
+    /// The in-game timer started running again
+    Resume(f32),
+}
+
+impl AutosplitterEvent {
+    /// The LiveSplit Server commands describing this event, in the order they should be sent
+    pub fn commands(&self) -> Vec<String> {
+        match *self {
+            AutosplitterEvent::Start => vec![
+                "starttimer".to_string(),
+                "initgametime".to_string(),
+                "pausegametime".to_string(),
+            ],
+            AutosplitterEvent::Split(time) => {
+                vec![
+                    format!("setgametime {}", format_lss_time(time)),
+                    "split".to_string(),
+                ]
+            }
+            AutosplitterEvent::Reset => vec!["reset".to_string()],
+            AutosplitterEvent::Death(time) => {
+                vec![format!("setgametime {}", format_lss_time(time))]
+            }
+            AutosplitterEvent::Pause(time) => vec![
+                "pausegametime".to_string(),
+                format!("setgametime {}", format_lss_time(time)),
+            ],
+            AutosplitterEvent::Resume(time) => vec![
+                format!("setgametime {}", format_lss_time(time)),
+                "unpausegametime".to_string(),
+            ],
+        }
+    }
+}
+
+fn connect_to_livesplit(mut connection: ResMut<LiveSplitConnection>, time: Res<Time>) {
+    if connection.stream.is_some() {
+        return;
+    }
+    connection.reconnect.tick(time.delta());
+    if !connection.reconnect.finished() {
+        return;
+    }
+    connection.reconnect.reset();
+    let address = connection.address;
+    // writes never wait on LiveSplit, a connection that can't keep up is dropped instead
+    match TcpStream::connect_timeout(&address, CONNECT_TIMEOUT).and_then(|stream| {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+        Ok(stream)
+    }) {
+        Ok(stream) => {
+            info!("connected to LiveSplit Server at {address}");
+            connection.stream = Some(stream);
+        }
+        Err(e) => debug!("could not connect to LiveSplit Server at {address}: {e}"),
+    }
+}
+
+fn send_state_transition_events(
+    mut transition_events: EventReader<StateTransitionEvent<LevelLoadingState>>,
+    mut autosplitter_events: EventWriter<AutosplitterEvent>,
+) {
+    for transition in transition_events.read() {
+        match (&transition.exited, &transition.entered) {
+            (Some(LevelLoadingState::MainMenu), Some(LevelLoadingState::Loading)) => {
+                autosplitter_events.send(AutosplitterEvent::Start);
+            }
+            (
+                Some(LevelLoadingState::Loaded | LevelLoadingState::EndScreen),
+                Some(LevelLoadingState::MainMenu),
+            ) => {
+                autosplitter_events.send(AutosplitterEvent::Reset);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn send_level_completed_events(
+    mut level_completed_events: EventReader<LevelCompletedEvent>,
+    mut autosplitter_events: EventWriter<AutosplitterEvent>,
+    speedrun_timer: Res<SpeedrunTimer>,
+) {
+    for LevelCompletedEvent in level_completed_events.read() {
+        autosplitter_events.send(AutosplitterEvent::Split(speedrun_timer.0.elapsed_secs()));
+    }
+}
+
+fn send_death_events(
+    mut autosplitter_events: EventWriter<AutosplitterEvent>,
+    death_count: Res<DeathCount>,
+    speedrun_timer: Res<SpeedrunTimer>,
+    mut last_death_count: Local<Option<usize>>,
+) {
+    // the first frame only picks up the count loaded from the save
+    if last_death_count.is_some_and(|last_death_count| death_count.0 > last_death_count) {
+        autosplitter_events.send(AutosplitterEvent::Death(speedrun_timer.0.elapsed_secs()));
+    }
+    *last_death_count = Some(death_count.0);
+}
+
+/// Sends [`AutosplitterEvent::Pause`] and [`AutosplitterEvent::Resume`] whenever the in-game timer
+/// stops or starts ticking
+fn send_game_time_events(
+    mut autosplitter_events: EventWriter<AutosplitterEvent>,
+    speedrun_timer: Res<SpeedrunTimer>,
+    mut last_elapsed: Local<Option<f32>>,
+    mut running: Local<bool>,
+) {
+    let elapsed = speedrun_timer.0.elapsed_secs();
+    let now_running = last_elapsed.is_some_and(|last_elapsed| elapsed > last_elapsed);
+    if now_running != *running {
+        autosplitter_events.send(if now_running {
+            AutosplitterEvent::Resume(elapsed)
+        } else {
+            AutosplitterEvent::Pause(elapsed)
+        });
+        *running = now_running;
+    }
+    *last_elapsed = Some(elapsed);
+}
+
+fn write_autosplitter_events(
+    mut connection: ResMut<LiveSplitConnection>,
+    mut autosplitter_events: EventReader<AutosplitterEvent>,
+) {
+    let mut message = String::new();
+    for event in autosplitter_events.read() {
+        for command in event.commands() {
+            message.push_str(&command);
+            message.push_str("\r\n");
+        }
+    }
+    if message.is_empty() {
+        return;
+    }
+    let Some(stream) = connection.stream.as_mut() else {
+        return;
+    };
+    if let Err(e) = stream.write_all(message.as_bytes()) {
+        if e.kind() == io::ErrorKind::WouldBlock {
+            warn!("LiveSplit Server isn't keeping up, reconnecting");
+        } else {
+            warn!("lost the connection to LiveSplit Server: {e}");
+        }
+        connection.stream = None;
+    }
+}
@@ -0,0 +1,332 @@
+//! Checks LDtk projects for mistakes that would break their levels in game, without opening a
+//! window. Checks every project in the assets folder unless given paths to projects.
+//!
+//! Also searches every level for a way to its goal, see [`solvability`]. That takes a while, so
+//! it's best run with optimizations:
+//!
+//! `cargo run --release --bin validate-levels [project.ldtk...]`
+
+mod solvability;
+
+use std::{
+    collections::HashSet,
+    fs, io,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    thread,
+};
+
+use bevy_ecs_ldtk::{
+    ldtk::{EntityInstance, LdtkJson, Level},
+    prelude::LdtkFields,
+};
+use cyclic_tower::entities::{lever::LEVER_IDS, platform::PLATFORM_IDS};
+use solvability::Solvability;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+enum LoadError {
+    #[error("could not read project: {0}")]
+    Io(#[from] io::Error),
+    #[error("could not parse project: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+fn main() -> ExitCode {
+    let mut projects: Vec<PathBuf> = std::env::args().skip(1).map(PathBuf::from).collect();
+    if projects.is_empty() {
+        match find_projects(&Path::new(env!("CARGO_MANIFEST_DIR")).join("assets")) {
+            Ok(found) => projects = found,
+            Err(e) => {
+                eprintln!("could not list the assets folder: {e}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    let mut problem_count = 0;
+    for project in projects.iter() {
+        let levels = match load_levels(project) {
+            Ok(levels) => levels,
+            Err(e) => {
+                println!("{}: {e}", project.display());
+                problem_count += 1;
+                continue;
+            }
+        };
+        // searching a level takes a while, so all the levels of a project are searched at once
+        let solvabilities: Vec<Option<Solvability>> = thread::scope(|scope| {
+            let searches: Vec<_> = levels
+                .iter()
+                .map(|level| scope.spawn(|| solvability::check_level(level)))
+                .collect();
+            searches
+                .into_iter()
+                .map(|search| search.join().expect("level search should not panic"))
+                .collect()
+        });
+        for (level, solvability) in levels.iter().zip(solvabilities) {
+            let problems = validate_level(level);
+            // the search is approximate, so these are only worth a look
+            match solvability {
+                Some(Solvability::Unreachable) => println!(
+                    "{}: {}: no way to the Goal found, check it by hand",
+                    project.display(),
+                    level.identifier
+                ),
+                Some(Solvability::TooComplex) => println!(
+                    "{}: {}: too complex to check whether the Goal can be reached",
+                    project.display(),
+                    level.identifier
+                ),
+                Some(Solvability::NotChecked { entity }) => println!(
+                    "{}: {}: not checked: contains a {entity}",
+                    project.display(),
+                    level.identifier
+                ),
+                Some(Solvability::Reachable { required }) if !required.is_empty() => println!(
+                    "{}: {}: reaching the Goal requires {}",
+                    project.display(),
+                    level.identifier,
+                    required.join(", ")
+                ),
+                Some(Solvability::Reachable { .. }) | None => {}
+            }
+            for problem in problems {
+                println!("{}: {}: {problem}", project.display(), level.identifier);
+                problem_count += 1;
+            }
+        }
+    }
+
+    if problem_count == 0 {
+        println!("checked {} projects, no problems found", projects.len());
+        ExitCode::SUCCESS
+    } else {
+        println!("found {problem_count} problems");
+        ExitCode::FAILURE
+    }
+}
+
+fn find_projects(assets: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut projects = Vec::new();
+    for entry in fs::read_dir(assets)? {
+        let path = entry?.path();
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "ldtk")
+        {
+            projects.push(path);
+        }
+    }
+    projects.sort();
+    Ok(projects)
+}
+
+/// Reads every level of a project, including levels saved in separate files
+fn load_levels(project: &Path) -> Result<Vec<Level>, LoadError> {
+    let ldtk_json: LdtkJson = serde_json::from_slice(&fs::read(project)?)?;
+    if !ldtk_json.external_levels {
+        return Ok(ldtk_json.levels);
+    }
+    let project_dir = project.parent().unwrap_or(Path::new(""));
+    ldtk_json
+        .levels
+        .into_iter()
+        .map(|level| match &level.external_rel_path {
+            Some(path) => Ok(serde_json::from_slice(&fs::read(project_dir.join(path))?)?),
+            None => Ok(level),
+        })
+        .collect()
+}
+
+fn validate_level(level: &Level) -> Vec<String> {
+    let mut problems = Vec::new();
+    let entities: Vec<&EntityInstance> = level
+        .layer_instances
+        .iter()
+        .flatten()
+        .flat_map(|layer| &layer.entity_instances)
+        .collect();
+    let count = |identifiers: &[&str]| {
+        entities
+            .iter()
+            .filter(|entity| identifiers.contains(&entity.identifier.as_str()))
+            .count()
+    };
+
+    let players = count(&["Player"]);
+    if players != 1 {
+        problems.push(format!("has {players} Player entities, expected 1"));
+    }
+    // older level packs call the goal "Exit"
+    let goals = count(&["Goal", "Exit"]);
+    if goals != 1 {
+        problems.push(format!("has {goals} Goal entities, expected 1"));
+    }
+    let keys = count(&["Key"]);
+    let doors = count(&["LockedDoor"]);
+    if keys < doors {
+        problems.push(format!(
+            "has {doors} LockedDoor entities but only {keys} Key entities"
+        ));
+    }
+
+    let platform_ids: HashSet<i32> = entities
+        .iter()
+        .filter(|entity| entity.identifier == "LeverPlatform")
+        .filter_map(|entity| entity.get_int_field("platform_id").ok().copied())
+        .collect();
+    for entity in entities.iter() {
+        let name = format!("{} at {}", entity.identifier, entity.grid);
+        match entity.identifier.as_str() {
+            "Lever" => match entity.get_int_field("lever_id") {
+                Ok(id) => {
+                    if !LEVER_IDS.contains(id) {
+                        problems.push(format!(
+                            "{name} has lever_id {id}, expected {} to {}",
+                            LEVER_IDS.start(),
+                            LEVER_IDS.end()
+                        ));
+                    }
+                    if !platform_ids.contains(id) {
+                        problems.push(format!(
+                            "{name} has lever_id {id}, but no LeverPlatform has that platform_id"
+                        ));
+                    }
+                }
+                Err(e) => problems.push(format!("{name}: {e}")),
+            },
+            "LeverPlatform" => {
+                match entity.get_int_field("platform_id") {
+                    Ok(id) if !PLATFORM_IDS.contains(id) => problems.push(format!(
+                        "{name} has platform_id {id}, expected {} to {}",
+                        PLATFORM_IDS.start(),
+                        PLATFORM_IDS.end()
+                    )),
+                    Ok(_) => {}
+                    Err(e) => problems.push(format!("{name}: {e}")),
+                }
+                if let Err(e) = entity.get_bool_field("init_state") {
+                    problems.push(format!("{name}: {e}"));
+                }
+            }
+            "Sign" => match entity.get_string_field("text") {
+                Ok(text) if text.trim().is_empty() => {
+                    problems.push(format!("{name} has an empty text"));
+                }
+                Ok(_) => {}
+                Err(e) => problems.push(format!("{name}: {e}")),
+            },
+            _ => {}
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_ecs_ldtk::ldtk::{FieldInstance, FieldValue, LayerInstance};
+
+    /// A level with a single entity layer holding `entities`
+    fn level(entities: Vec<EntityInstance>) -> Level {
+        Level {
+            identifier: "Level_0".into(),
+            layer_instances: Some(vec![LayerInstance {
+                entity_instances: entities,
+                ..Default::default()
+            }]),
+            ..Default::default()
+        }
+    }
+
+    fn entity(identifier: &str, fields: Vec<(&str, FieldValue)>) -> EntityInstance {
+        EntityInstance {
+            identifier: identifier.into(),
+            field_instances: fields
+                .into_iter()
+                .map(|(identifier, value)| FieldInstance {
+                    identifier: identifier.into(),
+                    tile: None,
+                    field_instance_type: String::new(),
+                    value,
+                    def_uid: 0,
+                    real_editor_values: Vec::new(),
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn lever(id: i32) -> EntityInstance {
+        entity("Lever", vec![("lever_id", FieldValue::Int(Some(id)))])
+    }
+
+    fn platform(id: i32) -> EntityInstance {
+        entity(
+            "LeverPlatform",
+            vec![
+                ("platform_id", FieldValue::Int(Some(id))),
+                ("init_state", FieldValue::Bool(false)),
+            ],
+        )
+    }
+
+    /// Problems of a level with a Player and a Goal besides `entities`
+    fn problems(entities: Vec<EntityInstance>) -> Vec<String> {
+        let mut all = vec![entity("Player", vec![]), entity("Goal", vec![])];
+        all.extend(entities);
+        validate_level(&level(all))
+    }
+
+    #[test]
+    fn finds_nothing_wrong_with_a_valid_level() {
+        let entities = vec![
+            lever(1),
+            platform(1),
+            entity("Key", vec![]),
+            entity("LockedDoor", vec![]),
+        ];
+        assert_eq!(problems(entities), Vec::<String>::new());
+    }
+
+    #[test]
+    fn needs_exactly_one_player_and_goal() {
+        assert_eq!(
+            validate_level(&level(vec![])),
+            [
+                "has 0 Player entities, expected 1",
+                "has 0 Goal entities, expected 1"
+            ]
+        );
+        assert_eq!(
+            problems(vec![entity("Player", vec![]), entity("Exit", vec![])]),
+            [
+                "has 2 Player entities, expected 1",
+                "has 2 Goal entities, expected 1"
+            ]
+        );
+    }
+
+    #[test]
+    fn needs_a_key_for_every_door() {
+        let entities = vec![
+            entity("Key", vec![]),
+            entity("LockedDoor", vec![]),
+            entity("LockedDoor", vec![]),
+        ];
+        assert_eq!(
+            problems(entities),
+            ["has 2 LockedDoor entities but only 1 Key entities"]
+        );
+    }
+
+    #[test]
+    fn levers_need_a_valid_id_and_a_platform() {
+        let problems = problems(vec![lever(0), platform(2)]);
+        assert_eq!(problems.len(), 2);
+        assert!(problems[0].ends_with("has lever_id 0, expected 1 to 4"));
+        assert!(problems[1].ends_with("has lever_id 0, but no LeverPlatform has that platform_id"));
+    }
+}
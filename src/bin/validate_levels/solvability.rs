@@ -0,0 +1,1022 @@
+//! Searches for a way to the goal of a level, by simulating the player's movement tick by tick
+//! against the level's walls, hazards and entities, for every combination of keys, doors, levers
+//! and pickups the player can get to.
+//!
+//! The search is approximate, so it can be wrong either way. It leaves some moves out: inputs are
+//! held for a few ticks at a time, so jumps are only let go of between them, and bodies close
+//! together are treated as one. It also makes some easier: the player reaches running speed
+//! instantly, jump tokens come back as soon as the player lands, and the backwards barrier, echoes
+//! and cycle filters are ignored. A goal it can't reach is worth a look, not proof of a broken
+//! level.
+
+use std::collections::{hash_map::Entry, HashMap, HashSet, VecDeque};
+use std::hash::{BuildHasherDefault, Hasher};
+
+use bevy_ecs_ldtk::{
+    ldtk::{EntityInstance, Level, Type},
+    prelude::LdtkFields,
+};
+use std::time::Duration;
+
+use cyclic_tower::{
+    physics::{FIXED_TIMESTEP_HZ, PIXELS_PER_METER},
+    player::{
+        abilities::wall_jump::{WALL_JUMP_COOLDOWN, WALL_SLIDE_SPEED},
+        jump::JumpTuning,
+        COYOTE_TIME, JUMP_COOLDOWN, RUN_ACCELERATION, RUN_DRAG,
+    },
+};
+
+const TICK: f32 = 1. / FIXED_TIMESTEP_HZ as f32;
+/// Rapier's default gravity
+const GRAVITY: f32 = -9.81 * PIXELS_PER_METER;
+/// Where the player's horizontal speed settles when running
+const RUN_SPEED: f32 = RUN_ACCELERATION / (RUN_DRAG - 1.);
+const JUMP_COOLDOWN_TICKS: u8 = ticks(JUMP_COOLDOWN);
+const COYOTE_TICKS: u8 = ticks(COYOTE_TIME);
+const WALL_JUMP_COOLDOWN_TICKS: u8 = ticks(WALL_JUMP_COOLDOWN);
+
+// the player's collider around its translation, a little smaller to stay on the generous side
+const BODY_HALF_WIDTH: f32 = 7.9;
+const BODY_BOTTOM: f32 = -6.9;
+const BODY_TOP: f32 = 2.9;
+/// How far sideways the rounded corners of the collider let it slide past an edge
+const CORNER_SLIDES: [f32; 4] = [-1., 1., -2., 2.];
+
+/// Bodies to search before giving up on a level, which takes around half a minute
+const MAX_BODIES: usize = 6_000_000;
+/// How many ticks the player holds each input for, a little less precise than a player's hands
+const TICKS_PER_INPUT: usize = 3;
+
+const WRAP_FIELD: &str = "wrap";
+
+const fn ticks(duration: Duration) -> u8 {
+    (duration.as_millis() * FIXED_TIMESTEP_HZ as u128 / 1000) as u8
+}
+
+/// What the player does with the jump key at the start of an input
+#[derive(Clone, Copy, PartialEq)]
+enum JumpKey {
+    /// Keeps holding it, or keeps it let go
+    Unchanged,
+    Press,
+    /// Lets go of it partway up a jump, for a short hop
+    Release,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Solvability {
+    /// The goal can be reached, and every way to it uses these entities
+    Reachable { required: Vec<String> },
+    /// The search found no way to the goal, though the player may still find one
+    Unreachable,
+    /// Too many combinations of doors, levers and pickups to search them all
+    TooComplex,
+    /// Not searched, since the level has an entity the search doesn't simulate
+    NotChecked { entity: String },
+}
+
+/// Entities the search doesn't simulate, so whether the goal needs them can't be told
+const UNSIMULATED_ENTITIES: [&str; 1] = ["Dash"];
+
+/// Whether the goal of `level` can be reached, or `None` if it has no player, goal or walls
+pub fn check_level(level: &Level) -> Option<Solvability> {
+    if let Some(entity) = level
+        .layer_instances
+        .iter()
+        .flatten()
+        .flat_map(|layer| &layer.entity_instances)
+        .find(|entity| UNSIMULATED_ENTITIES.contains(&entity.identifier.as_str()))
+    {
+        return Some(Solvability::NotChecked {
+            entity: entity.identifier.clone(),
+        });
+    }
+    LevelMap::from_level(level).map(|map| map.solvability())
+}
+
+/// Rectangle in level pixels, with y pointing up like in game
+#[derive(Clone, Copy, Debug)]
+struct Rect {
+    left: f32,
+    bottom: f32,
+    right: f32,
+    top: f32,
+}
+
+impl Rect {
+    fn centered(x: f32, y: f32, half_width: f32, half_height: f32) -> Self {
+        Self {
+            left: x - half_width,
+            bottom: y - half_height,
+            right: x + half_width,
+            top: y + half_height,
+        }
+    }
+
+    fn shifted(self, x: f32, y: f32) -> Self {
+        Self {
+            left: self.left + x,
+            bottom: self.bottom + y,
+            right: self.right + x,
+            top: self.top + y,
+        }
+    }
+
+    fn overlaps(&self, other: &Rect) -> bool {
+        self.left < other.right
+            && other.left < self.right
+            && self.bottom < other.top
+            && other.bottom < self.top
+    }
+}
+
+/// Which pixels of a level are filled, summed up so any rectangle can be checked at once
+struct PixelMap {
+    width: i32,
+    sums: Vec<u32>,
+}
+
+impl PixelMap {
+    fn new(width: i32, height: i32, rects: impl Iterator<Item = Rect>) -> Self {
+        let mut filled = vec![false; (width * height) as usize];
+        for rect in rects {
+            let left = (rect.left.floor() as i32).max(0);
+            let right = (rect.right.ceil() as i32).min(width);
+            let bottom = (rect.bottom.floor() as i32).max(0);
+            let top = (rect.top.ceil() as i32).min(height);
+            for y in bottom..top {
+                for x in left..right {
+                    filled[(y * width + x) as usize] = true;
+                }
+            }
+        }
+        let stride = width as usize + 1;
+        let mut sums = vec![0; stride * (height as usize + 1)];
+        for y in 0..height as usize {
+            let mut row = 0;
+            for x in 0..width as usize {
+                row += filled[y * width as usize + x] as u32;
+                sums[(y + 1) * stride + x + 1] = sums[y * stride + x + 1] + row;
+            }
+        }
+        Self { width, sums }
+    }
+
+    /// Whether any pixel in `left..right` and `bottom..top` is filled, all inside the map
+    fn any_in(&self, left: i32, bottom: i32, right: i32, top: i32) -> bool {
+        let stride = self.width as usize + 1;
+        let sum = |x: i32, y: i32| self.sums[y as usize * stride + x as usize];
+        sum(right, top) + sum(left, bottom) > sum(left, top) + sum(right, bottom)
+    }
+}
+
+/// The parts of `start..end` inside `0..size`, wrapped around if `wrap`
+fn spans(start: i32, end: i32, size: i32, wrap: bool) -> [(i32, i32); 2] {
+    if !wrap {
+        return [(start.clamp(0, size), end.clamp(0, size)), (0, 0)];
+    }
+    let start_wrapped = start.rem_euclid(size);
+    let end_wrapped = start_wrapped + (end - start).min(size);
+    if end_wrapped <= size {
+        [(start_wrapped, end_wrapped), (0, 0)]
+    } else {
+        [(start_wrapped, size), (0, end_wrapped - size)]
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum EntityKind {
+    Goal,
+    Key,
+    LockedDoor,
+    Lever(i32),
+    DoubleJump,
+    WallJump,
+    JumpToken,
+}
+
+/// An entity the player does something with by touching it
+struct Entity {
+    name: String,
+    kind: EntityKind,
+    sensor: Rect,
+    /// The solid part of a locked door
+    collider: Option<Rect>,
+}
+
+impl Entity {
+    /// An entity centered on `x` and `y`, with the same sensor size as the entity's own
+    fn new(name: String, kind: EntityKind, x: f32, y: f32) -> Self {
+        let (sensor, collider) = match kind {
+            EntityKind::Goal => (Rect::centered(x, y, 24., 16.), None),
+            EntityKind::Key => (Rect::centered(x, y, 5., 5.), None),
+            EntityKind::LockedDoor => (
+                Rect::centered(x - 4., y, 16., 16.),
+                Some(Rect::centered(x - 4., y, 2., 16.)),
+            ),
+            EntityKind::Lever(_) => (Rect::centered(x, y, 8., 8.), None),
+            EntityKind::DoubleJump | EntityKind::WallJump | EntityKind::JumpToken => {
+                (Rect::centered(x, y, 7., 7.), None)
+            }
+        };
+        Self {
+            name,
+            kind,
+            sensor,
+            collider,
+        }
+    }
+}
+
+struct Platform {
+    id: i32,
+    active: bool,
+    rect: Rect,
+}
+
+/// What the player has done so far, with one bit per entity used up and per platform id flipped
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+struct Progress {
+    used: u64,
+    flipped: u64,
+}
+
+/// The solid parts of a level given some [`Progress`], and what the player can do in it
+struct Geometry {
+    solids: PixelMap,
+    max_extra_jumps: u8,
+    wall_jump: bool,
+}
+
+/// The buckets and counters of a [`Body`] packed together, so the millions of them a level can
+/// take are cheap to hash
+type BodyKey = u128;
+
+/// Hashes [`BodyKey`]s with a multiplication, a lot faster than the default hasher
+#[derive(Default)]
+struct BodyKeyHasher(u64);
+
+impl Hasher for BodyKeyHasher {
+    fn finish(&self) -> u64 {
+        // the low bits of a product only depend on the low bits of the key, so mix in the high ones
+        self.0 ^ self.0 >> 32
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.write_u64(byte as u64);
+        }
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.0 = (self.0 ^ value).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    }
+
+    fn write_u128(&mut self, value: u128) {
+        self.write_u64(value as u64);
+        self.write_u64((value >> 64) as u64);
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Body {
+    x: f32,
+    y: f32,
+    vy: f32,
+    /// Whether the player jumped since they last landed
+    holding_jump: bool,
+    extra_jumps: u8,
+    air_jumps: u8,
+    jump_cooldown: u8,
+    coyote: u8,
+    wall_jump_cooldown: [u8; 2],
+    /// Jump tokens taken since the player last landed
+    used_tokens: u64,
+}
+
+impl Body {
+    fn standing_at(x: f32, y: f32) -> Self {
+        Self {
+            x,
+            y,
+            vy: 0.,
+            holding_jump: false,
+            extra_jumps: 0,
+            air_jumps: 0,
+            jump_cooldown: 0,
+            coyote: 0,
+            wall_jump_cooldown: [0; 2],
+            used_tokens: 0,
+        }
+    }
+
+    fn rect(&self) -> Rect {
+        Rect {
+            left: self.x - BODY_HALF_WIDTH,
+            bottom: self.y + BODY_BOTTOM,
+            right: self.x + BODY_HALF_WIDTH,
+            top: self.y + BODY_TOP,
+        }
+    }
+
+    /// Bodies with the same key are treated as the same state, apart from their vertical velocity
+    fn key(&self) -> BodyKey {
+        let x = (self.x / 4.).floor() as i16 as u16 as u64;
+        let y = (self.y / 4.).floor() as i16 as u16 as u64;
+        let counters = [
+            self.extra_jumps,
+            self.air_jumps,
+            self.jump_cooldown.div_ceil(4),
+            (self.coyote > 0) as u8,
+            // only worth telling apart on the way up, where letting go cuts the jump short
+            (self.holding_jump && self.vy > 0.) as u8,
+            self.wall_jump_cooldown[0].div_ceil(24),
+            self.wall_jump_cooldown[1].div_ceil(24),
+        ]
+        .into_iter()
+        .fold(0, |packed, counter| packed << 4 | counter.min(15) as u64);
+        (self.used_tokens as u128) << 64 | (x << 48 | y << 32 | counters) as u128
+    }
+}
+
+struct LevelMap {
+    width: i32,
+    height: i32,
+    wrap_x: bool,
+    wrap_y: bool,
+    terrain: Vec<Rect>,
+    hazards: PixelMap,
+    platforms: Vec<Platform>,
+    entities: Vec<Entity>,
+    spawn: (f32, f32),
+    jump: JumpTuning,
+}
+
+impl LevelMap {
+    fn from_level(level: &Level) -> Option<Self> {
+        let height = level.px_hei as f32;
+        // LDtk positions start at the top left corner, the game's at the bottom left
+        let center = |entity: &EntityInstance| {
+            (
+                entity.px.x as f32 + entity.width as f32 * (0.5 - entity.pivot.x),
+                height - entity.px.y as f32 - entity.height as f32 * (0.5 - entity.pivot.y),
+            )
+        };
+
+        let mut terrain = Vec::new();
+        let mut hazards = Vec::new();
+        let mut platforms = Vec::new();
+        let mut entities = Vec::new();
+        let mut spawn = None;
+        for layer in level.layer_instances.iter().flatten() {
+            if layer.layer_instance_type == Type::IntGrid {
+                let size = layer.grid_size as f32;
+                for (index, value) in layer.int_grid_csv.iter().enumerate() {
+                    let x = (index as i32 % layer.c_wid) as f32 * size + size / 2.;
+                    let y = height - (index as i32 / layer.c_wid) as f32 * size - size / 2.;
+                    // same values and collider sizes as the cells registered in `level`
+                    match value {
+                        1 => terrain.push(Rect::centered(x, y, size / 2., size / 2.)),
+                        2 => hazards.push(Rect::centered(x, y, 8., 6.)),
+                        4 => hazards.push(Rect::centered(x, y, 4.5, 4.5)),
+                        _ => {}
+                    }
+                }
+            }
+
+            for entity in layer.entity_instances.iter() {
+                let (x, y) = center(entity);
+                let name = format!("{} at {}", entity.identifier, entity.grid);
+                let mut add = |kind| entities.push(Entity::new(name.clone(), kind, x, y));
+                match entity.identifier.as_str() {
+                    "Player" => spawn = Some((x, y)),
+                    "Goal" | "Exit" => add(EntityKind::Goal),
+                    "Key" => add(EntityKind::Key),
+                    "LockedDoor" => add(EntityKind::LockedDoor),
+                    "Lever" => {
+                        if let Ok(&id @ 0..64) = entity.get_int_field("lever_id") {
+                            add(EntityKind::Lever(id));
+                        }
+                    }
+                    "DoubleJump" => add(EntityKind::DoubleJump),
+                    "WallJump" => add(EntityKind::WallJump),
+                    "JumpToken" => add(EntityKind::JumpToken),
+                    "LeverPlatform" => {
+                        if let Ok(&id @ 0..64) = entity.get_int_field("platform_id") {
+                            platforms.push(Platform {
+                                id,
+                                active: entity
+                                    .get_bool_field("init_state")
+                                    .copied()
+                                    .unwrap_or(true),
+                                rect: Rect::centered(x, y, 8., 8.),
+                            });
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        if terrain.is_empty()
+            || entities.len() > 64
+            || !entities
+                .iter()
+                .any(|entity| entity.kind == EntityKind::Goal)
+        {
+            return None;
+        }
+
+        let wrap = level
+            .get_maybe_enum_field(WRAP_FIELD)
+            .ok()
+            .and_then(Option::as_ref)
+            .map(String::as_str);
+        let (width, height) = (level.px_wid, level.px_hei);
+        Some(Self {
+            width,
+            height,
+            wrap_x: !matches!(wrap, Some("Vertical")),
+            wrap_y: matches!(wrap, Some("Vertical" | "Both")),
+            terrain,
+            hazards: PixelMap::new(width, height, hazards.into_iter()),
+            platforms,
+            entities,
+            spawn: spawn?,
+            jump: JumpTuning::default(),
+        })
+    }
+
+    fn solvability(&self) -> Solvability {
+        let Some(progresses) = self.explore_progresses() else {
+            return Solvability::TooComplex;
+        };
+        if !reaches_goal(&progresses, None) {
+            return Solvability::Unreachable;
+        }
+        let required = (0..self.entities.len())
+            .filter(|&entity| !reaches_goal(&progresses, Some(entity)))
+            .map(|entity| self.entities[entity].name.clone())
+            .collect();
+        Solvability::Reachable { required }
+    }
+
+    fn geometry(&self, progress: Progress) -> Geometry {
+        let closed_doors =
+            self.entities.iter().enumerate().filter_map(|(i, entity)| {
+                entity.collider.filter(|_| progress.used & (1 << i) == 0)
+            });
+        let active_platforms = self
+            .platforms
+            .iter()
+            .filter(|platform| platform.active != (progress.flipped >> platform.id & 1 == 1))
+            .map(|platform| platform.rect);
+        let used = |kind| {
+            self.entities
+                .iter()
+                .enumerate()
+                .filter(|&(i, entity)| entity.kind == kind && progress.used & (1 << i) != 0)
+                .count()
+        };
+        Geometry {
+            solids: PixelMap::new(
+                self.width,
+                self.height,
+                self.terrain
+                    .iter()
+                    .copied()
+                    .chain(closed_doors)
+                    .chain(active_platforms),
+            ),
+            max_extra_jumps: used(EntityKind::DoubleJump) as u8,
+            wall_jump: used(EntityKind::WallJump) > 0,
+        }
+    }
+
+    /// Whether `rect` overlaps `map`, with the sides of levels that don't wrap around counting as
+    /// filled if `walled`
+    fn hits(&self, map: &PixelMap, rect: Rect, walled: bool) -> bool {
+        let (left, right) = (rect.left.floor() as i32, rect.right.ceil() as i32);
+        if walled && !self.wrap_x && (left < 0 || right > self.width) {
+            return true;
+        }
+        let (bottom, top) = (rect.bottom.floor() as i32, rect.top.ceil() as i32);
+        if left >= 0 && right <= self.width && bottom >= 0 && top <= self.height {
+            return map.any_in(left, bottom, right, top);
+        }
+        for (left, right) in spans(left, right, self.width, self.wrap_x) {
+            for (bottom, top) in spans(bottom, top, self.height, self.wrap_y) {
+                if left < right && bottom < top && map.any_in(left, bottom, right, top) {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// Whether `body` touches `sensor`, including across the edges the level wraps around
+    fn touches(&self, body: &Rect, sensor: &Rect) -> bool {
+        let width = self.width as f32;
+        let height = self.height as f32;
+        let xs: &[f32] = if self.wrap_x {
+            &[0., -width, width]
+        } else {
+            &[0.]
+        };
+        let ys: &[f32] = if self.wrap_y {
+            &[0., -height, height]
+        } else {
+            &[0.]
+        };
+        xs.iter()
+            .any(|&x| ys.iter().any(|&y| body.shifted(x, y).overlaps(sensor)))
+    }
+
+    /// Moves `body` by `distance` along one axis until it hits something, returning whether it did
+    fn travel(&self, geometry: &Geometry, body: &mut Body, distance: f32, vertical: bool) -> bool {
+        let (x, y) = if vertical {
+            (0., distance)
+        } else {
+            (distance, 0.)
+        };
+        let start = body.rect();
+        let end = start.shifted(x, y);
+        let swept = Rect {
+            left: start.left.min(end.left),
+            bottom: start.bottom.min(end.bottom),
+            right: start.right.max(end.right),
+            top: start.top.max(end.top),
+        };
+        // most moves don't hit anything, so only go pixel by pixel when something is in the way
+        if !self.hits(&geometry.solids, swept, true) {
+            body.x += x;
+            body.y += y;
+            return false;
+        }
+        let steps = distance.abs().ceil().max(1.);
+        let (step_x, step_y) = (x / steps, y / steps);
+        for _ in 0..steps as usize {
+            if self.hits(&geometry.solids, body.rect().shifted(step_x, step_y), true) {
+                // the rounded corners of the collider slide it past edges it only just catches
+                let slide = CORNER_SLIDES.into_iter().find(|&slide| {
+                    let rect = body.rect().shifted(slide, 0.);
+                    !self.hits(&geometry.solids, rect, true)
+                        && !self.hits(&geometry.solids, rect.shifted(0., step_y), true)
+                });
+                let Some(slide) = slide.filter(|_| vertical) else {
+                    return true;
+                };
+                body.x += slide;
+            }
+            body.x += step_x;
+            body.y += step_y;
+        }
+        false
+    }
+
+    /// One fixed tick of `move_player` and the physics step after it, or `None` if the jump isn't
+    /// possible or the player dies
+    fn step(
+        &self,
+        geometry: &Geometry,
+        mut body: Body,
+        direction: f32,
+        jump_key: JumpKey,
+    ) -> Option<Body> {
+        let solid = |rect| self.hits(&geometry.solids, rect, true);
+        let grounded = solid(body.rect().shifted(0., -1.));
+        let on_wall = [
+            solid(body.rect().shifted(-1., 0.)),
+            solid(body.rect().shifted(1., 0.)),
+        ];
+        if grounded {
+            body.coyote = COYOTE_TICKS;
+            body.extra_jumps = geometry.max_extra_jumps;
+            body.used_tokens = 0;
+        }
+        if grounded || on_wall.contains(&true) {
+            body.air_jumps = 0;
+        }
+        body.coyote = body.coyote.saturating_sub(1);
+        body.jump_cooldown = body.jump_cooldown.saturating_sub(1);
+        for cooldown in body.wall_jump_cooldown.iter_mut() {
+            *cooldown = cooldown.saturating_sub(1);
+        }
+
+        if jump_key == JumpKey::Press {
+            if body.jump_cooldown > 0 {
+                return None;
+            }
+            let wall = (0..2).find(|&side| {
+                geometry.wall_jump && on_wall[side] && body.wall_jump_cooldown[side] == 0
+            });
+            // jumps off the ground need no wall, double jump or jump token
+            if body.coyote == 0 {
+                if let Some(side) = wall {
+                    body.wall_jump_cooldown[side] = WALL_JUMP_COOLDOWN_TICKS;
+                    body.extra_jumps = geometry.max_extra_jumps;
+                } else if body.extra_jumps > 0 {
+                    body.extra_jumps -= 1;
+                } else if body.air_jumps > 0 {
+                    body.air_jumps -= 1;
+                } else {
+                    return None;
+                }
+            }
+            body.vy = self.jump.velocity;
+            body.jump_cooldown = JUMP_COOLDOWN_TICKS;
+            body.holding_jump = true;
+        } else if jump_key == JumpKey::Release {
+            body.holding_jump = false;
+            if body.vy > 0. {
+                body.vy *= self.jump.release_multiplier;
+            }
+        } else if grounded && body.vy <= 0. {
+            body.holding_jump = false;
+        }
+        body.vy = body.vy.max(-self.jump.max_fall_speed);
+        let gravity = GRAVITY * self.jump.gravity_scale(body.vy, body.holding_jump);
+        if geometry.wall_jump && on_wall.contains(&true) && body.vy < -WALL_SLIDE_SPEED {
+            body.vy = -WALL_SLIDE_SPEED;
+        }
+
+        body.vy += gravity * TICK;
+        self.travel(geometry, &mut body, direction * RUN_SPEED * TICK, false);
+        let fall = body.vy * TICK;
+        if self.travel(geometry, &mut body, fall, true) {
+            body.vy = 0.;
+        }
+        if self.wrap_x {
+            body.x = body.x.rem_euclid(self.width as f32);
+        }
+        if self.wrap_y {
+            body.y = body.y.rem_euclid(self.height as f32);
+        } else if body.rect().top < 0. {
+            return None;
+        }
+        if self.hits(&self.hazards, body.rect(), false) {
+            return None;
+        }
+
+        let rect = body.rect();
+        for (i, entity) in self.entities.iter().enumerate() {
+            if entity.kind == EntityKind::JumpToken
+                && body.used_tokens & (1 << i) == 0
+                && self.touches(&rect, &entity.sensor)
+            {
+                body.air_jumps += 1;
+                body.used_tokens |= 1 << i;
+            }
+        }
+        Some(body)
+    }
+
+    /// Steps `body` with an input held for a few ticks
+    fn hold_input(
+        &self,
+        geometry: &Geometry,
+        body: Body,
+        direction: f32,
+        jump_key: JumpKey,
+    ) -> Option<Body> {
+        (0..TICKS_PER_INPUT).try_fold(body, |body, tick| {
+            let jump_key = if tick == 0 {
+                jump_key
+            } else {
+                JumpKey::Unchanged
+            };
+            self.step(geometry, body, direction, jump_key)
+        })
+    }
+
+    /// How many keys the player holds with `progress`
+    fn held_keys(&self, progress: Progress) -> i32 {
+        self.entities
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| progress.used & (1 << i) != 0)
+            .fold(0, |keys, (_, entity)| match entity.kind {
+                EntityKind::Key => keys + 1,
+                EntityKind::LockedDoor => keys - 1,
+                _ => keys,
+            })
+    }
+
+    /// Whether touching entity `i` does something with `progress`
+    fn usable(&self, progress: Progress, i: usize) -> bool {
+        progress.used & (1 << i) == 0
+            && match self.entities[i].kind {
+                EntityKind::Goal | EntityKind::JumpToken => false,
+                EntityKind::LockedDoor => self.held_keys(progress) > 0,
+                _ => true,
+            }
+    }
+
+    /// Progress without the keys picked up, which don't change the walls or what the player can do
+    fn layout(&self, mut progress: Progress) -> Progress {
+        for (i, entity) in self.entities.iter().enumerate() {
+            if entity.kind == EntityKind::Key {
+                progress.used &= !(1 << i);
+            }
+        }
+        progress
+    }
+
+    /// Lets the player move from `starts` through the walls of `progress`, skipping bodies
+    /// `search` has seen before, and records the first body to touch each entity. Returns false if
+    /// it ran out of the `bodies` left to search.
+    fn explore(
+        &self,
+        progress: Progress,
+        starts: Vec<Body>,
+        search: &mut Search,
+        bodies: &mut usize,
+    ) -> bool {
+        if search.goal {
+            return true;
+        }
+        let geometry = self.geometry(progress);
+        let mut queue: VecDeque<Body> = starts
+            .into_iter()
+            .filter(|body| search.visit(body))
+            .collect();
+        while let Some(body) = queue.pop_front() {
+            let Some(left) = bodies.checked_sub(1) else {
+                return false;
+            };
+            *bodies = left;
+            let rect = body.rect();
+            for (i, entity) in self.entities.iter().enumerate() {
+                if search.touched & (1 << i) != 0 || !self.touches(&rect, &entity.sensor) {
+                    continue;
+                }
+                if entity.kind == EntityKind::Goal {
+                    search.goal = true;
+                    return true;
+                }
+                search.touched |= 1 << i;
+                search.first_touches.push((i, body));
+            }
+            for direction in [-1., 0., 1.] {
+                for jump_key in [JumpKey::Unchanged, JumpKey::Press, JumpKey::Release] {
+                    // letting go only changes anything on the way up a jump
+                    if jump_key == JumpKey::Release && !(body.holding_jump && body.vy > 0.) {
+                        continue;
+                    }
+                    let Some(next) = self.hold_input(&geometry, body, direction, jump_key) else {
+                        continue;
+                    };
+                    if search.visit(&next) {
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// Every progress the player can get to from the spawn, or `None` if there are too many
+    fn explore_progresses(&self) -> Option<HashMap<Progress, ProgressNode>> {
+        let spawn = Body::standing_at(self.spawn.0, self.spawn.1);
+        let mut searches: HashMap<Progress, Search> = HashMap::new();
+        let mut progresses: HashMap<Progress, ProgressNode> = HashMap::new();
+        let mut queue = VecDeque::from([(Progress::default(), vec![spawn])]);
+        let mut bodies = MAX_BODIES;
+        while let Some((progress, starts)) = queue.pop_front() {
+            // every set of keys held shares one search, as keys don't change the walls
+            let search = searches.entry(self.layout(progress)).or_default();
+            if !self.explore(progress, starts, search, &mut bodies) {
+                return None;
+            }
+            let node = progresses.entry(progress).or_default();
+            node.goal = search.goal;
+            for &(i, body) in search.first_touches.iter() {
+                if node.goal
+                    || !self.usable(progress, i)
+                    || node.next.iter().any(|&(used, _)| used == i)
+                {
+                    continue;
+                }
+                let mut next = progress;
+                // the player can also respawn at the checkpoints levers and doors set
+                let mut starts = vec![body];
+                match self.entities[i].kind {
+                    EntityKind::Lever(id) => {
+                        next.flipped ^= 1 << id;
+                        starts.push(Body::standing_at(body.x, body.y));
+                    }
+                    EntityKind::LockedDoor => {
+                        next.used |= 1 << i;
+                        starts.push(Body::standing_at(body.x, body.y));
+                    }
+                    _ => next.used |= 1 << i,
+                }
+                node.next.push((i, next));
+                queue.push_back((next, starts));
+            }
+        }
+        Some(progresses)
+    }
+}
+
+/// Where the player got to with some [`Progress`], or with several that share walls
+#[derive(Default)]
+struct Search {
+    /// The lowest and highest vertical velocity seen with each key
+    seen: HashMap<BodyKey, (f32, f32), BuildHasherDefault<BodyKeyHasher>>,
+    goal: bool,
+    /// Entities touched so far
+    touched: u64,
+    /// The first body to touch each entity
+    first_touches: Vec<(usize, Body)>,
+}
+
+impl Search {
+    /// Whether `body` is worth moving on from. Only the lowest and highest vertical velocities are
+    /// kept for each key: arcs starting in between stay between theirs, so they rarely get
+    /// anywhere new, and keeping every velocity takes millions of bodies on large levels.
+    fn visit(&mut self, body: &Body) -> bool {
+        match self.seen.entry(body.key()) {
+            Entry::Vacant(entry) => {
+                entry.insert((body.vy, body.vy));
+                true
+            }
+            Entry::Occupied(mut entry) => {
+                let (lowest, highest) = entry.get_mut();
+                if body.vy < *lowest {
+                    *lowest = body.vy;
+                } else if body.vy > *highest {
+                    *highest = body.vy;
+                } else {
+                    return false;
+                }
+                true
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct ProgressNode {
+    goal: bool,
+    /// The entity used to get to each next progress
+    next: Vec<(usize, Progress)>,
+}
+
+/// Whether some way through `progresses` gets to the goal without using the entity `disabled`.
+///
+/// Searches are shared by every way to a progress, so an entity only some of them use may not be
+/// reported as required.
+fn reaches_goal(progresses: &HashMap<Progress, ProgressNode>, disabled: Option<usize>) -> bool {
+    let mut seen = HashSet::from([Progress::default()]);
+    let mut queue = vec![Progress::default()];
+    while let Some(progress) = queue.pop() {
+        let node = &progresses[&progress];
+        if node.goal {
+            return true;
+        }
+        for &(i, next) in node.next.iter() {
+            if Some(i) != disabled && seen.insert(next) {
+                queue.push(next);
+            }
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CELL: f32 = 16.;
+
+    /// A level that doesn't wrap around, drawn with one character per cell: `#` for walls, `^` for
+    /// spikes, `P` for the player and `G`, `K`, `D`, `2` and `W` for the goal, keys, locked doors,
+    /// double jumps and wall jumps
+    fn level_map(rows: &[&str]) -> LevelMap {
+        let width = rows[0].len() as i32 * CELL as i32;
+        let height = rows.len() as i32 * CELL as i32;
+        let mut terrain = Vec::new();
+        let mut hazards = Vec::new();
+        let mut entities = Vec::new();
+        let mut spawn = (0., 0.);
+        for (row, cells) in rows.iter().enumerate() {
+            for (column, cell) in cells.chars().enumerate() {
+                let x = (column as f32 + 0.5) * CELL;
+                let y = height as f32 - (row as f32 + 0.5) * CELL;
+                let (identifier, kind) = match cell {
+                    '#' => {
+                        terrain.push(Rect::centered(x, y, CELL / 2., CELL / 2.));
+                        continue;
+                    }
+                    '^' => {
+                        hazards.push(Rect::centered(x, y, 4.5, 4.5));
+                        continue;
+                    }
+                    'P' => {
+                        spawn = (x, y);
+                        continue;
+                    }
+                    'G' => ("Goal", EntityKind::Goal),
+                    'K' => ("Key", EntityKind::Key),
+                    'D' => ("LockedDoor", EntityKind::LockedDoor),
+                    '2' => ("DoubleJump", EntityKind::DoubleJump),
+                    'W' => ("WallJump", EntityKind::WallJump),
+                    _ => continue,
+                };
+                let name = format!("{identifier} at [{column},{row}]");
+                entities.push(Entity::new(name, kind, x, y));
+            }
+        }
+        LevelMap {
+            width,
+            height,
+            wrap_x: false,
+            wrap_y: false,
+            terrain,
+            hazards: PixelMap::new(width, height, hazards.into_iter()),
+            platforms: Vec::new(),
+            entities,
+            spawn,
+            jump: JumpTuning::default(),
+        }
+    }
+
+    fn requires(names: &[&str]) -> Solvability {
+        Solvability::Reachable {
+            required: names.iter().map(|name| name.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn wide_gap_needs_a_double_jump() {
+        let gap = [
+            "####################",
+            "#..................#",
+            "#..................#",
+            "#..................#",
+            "#..................#",
+            "#P2.............G..#",
+            "###..........#######",
+        ];
+        assert_eq!(
+            level_map(&gap).solvability(),
+            requires(&["DoubleJump at [2,5]"])
+        );
+
+        let without_double_jump = gap.map(|row| row.replace('2', "."));
+        assert_eq!(
+            level_map(&without_double_jump.each_ref().map(String::as_str)).solvability(),
+            Solvability::Unreachable
+        );
+    }
+
+    #[test]
+    fn low_spikes_need_a_short_hop() {
+        let corridor = [
+            "############",
+            "#^^^^^^^^^^#",
+            "#..........#",
+            "#P.......G.#",
+            "####..######",
+            "...#..#.....",
+            "...#..#.....",
+            "...#..#.....",
+            "...####.....",
+        ];
+        assert_eq!(level_map(&corridor).solvability(), requires(&[]));
+
+        let mut level_map = level_map(&corridor);
+        level_map.jump.release_multiplier = 1.;
+        assert_eq!(level_map.solvability(), Solvability::Unreachable);
+    }
+
+    #[test]
+    fn door_needs_a_key() {
+        let door = ["##########", "#........#", "#P.K..D.G#", "##########"];
+        assert_eq!(
+            level_map(&door).solvability(),
+            requires(&["Key at [3,2]", "LockedDoor at [6,2]"])
+        );
+
+        let without_key = ["##########", "#........#", "#P....D.G#", "##########"];
+        assert_eq!(
+            level_map(&without_key).solvability(),
+            Solvability::Unreachable
+        );
+    }
+
+    #[test]
+    fn shaft_needs_a_wall_jump() {
+        let mut shaft = vec!["#######", "#G....#"];
+        shaft.extend(["####..#"; 10]);
+        shaft.extend(["#PW...#", "#######"]);
+        assert_eq!(
+            level_map(&shaft).solvability(),
+            requires(&["WallJump at [2,12]"])
+        );
+
+        shaft[12] = "#P....#";
+        assert_eq!(level_map(&shaft).solvability(), Solvability::Unreachable);
+    }
+}
@@ -5,8 +5,8 @@ use bevy::{
 };
 
 use crate::{
-    camera::HUD_RENDER_LAYER,
-    player::{PlayerInventory, PlayerMarker},
+    camera::HUD_RENDER_LAYER, entities::fields::LevelFieldErrors, level::RunSplits,
+    player::abilities::HudItems, splits::ComparisonSplits,
 };
 
 use super::{HudCameraMarker, CAMERA_UNIT_WIDTH};
@@ -17,10 +17,8 @@ pub struct HudIconMarker;
 #[derive(Debug, Copy, Clone)]
 pub enum HudIcon {
     None,
-    Key,
-    DoubleJump,
-    WallJump,
-    JumpToken,
+    /// Something the player carries, by the path of its icon
+    Item(&'static str),
     BgLeft,
     BgCenter,
     BgRight,
@@ -36,6 +34,14 @@ pub struct HudIconInfo {
 #[derive(Component)]
 pub struct HudTextMarker;
 
+/// Text in the top right corner showing how far ahead of or behind the comparison splits the run is
+#[derive(Component)]
+pub struct HudSplitDeltaMarker;
+
+/// Text in the bottom left corner listing the LDtk entity fields of the level that are broken
+#[derive(Component)]
+pub struct HudFieldErrorsMarker;
+
 /// Padding from the top left corner
 const HUD_PADDING: Vec2 = Vec2::new(30., -30.);
 const MAX_HUD_ICONS: usize = 15;
@@ -188,9 +194,86 @@ pub fn spawn_hud(
                 unit_height / 2. + HUD_PADDING.y,
                 0.,
             )));
+        parent
+            .spawn(Text2dBundle {
+                text: Text::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("Monocraft.ttf"),
+                        font_size: 18.,
+                        ..default()
+                    },
+                ),
+                text_anchor: Anchor::TopRight,
+                ..default()
+            })
+            .insert(HudSplitDeltaMarker)
+            .insert(HUD_RENDER_LAYER)
+            .insert(TransformBundle::from_transform(Transform::from_xyz(
+                unit_width / 2. - HUD_PADDING.x * pixel_scaling,
+                unit_height / 2. + HUD_PADDING.y * pixel_scaling,
+                0.,
+            )));
+        parent
+            .spawn(Text2dBundle {
+                text: Text::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("Monocraft.ttf"),
+                        font_size: 14.,
+                        color: Color::srgb(0.9, 0.3, 0.3),
+                    },
+                ),
+                text_anchor: Anchor::BottomLeft,
+                ..default()
+            })
+            .insert(HudFieldErrorsMarker)
+            .insert(HUD_RENDER_LAYER)
+            .insert(TransformBundle::from_transform(Transform::from_xyz(
+                -unit_width / 2. + HUD_PADDING.x * pixel_scaling,
+                -unit_height / 2. - HUD_PADDING.y * pixel_scaling,
+                0.,
+            )));
     });
 }
 
+/// Shows the difference between the run time at the last finished level and the same level in the
+/// comparison splits
+pub fn show_split_delta(
+    mut q_split_delta: Query<&mut Text, With<HudSplitDeltaMarker>>,
+    run_splits: Res<RunSplits>,
+    comparison_splits: Res<ComparisonSplits>,
+) {
+    let Ok(mut text) = q_split_delta.get_single_mut() else {
+        return;
+    };
+    let Some(delta) = run_splits.0.last().and_then(|split| {
+        comparison_splits
+            .run_time(&split.level_name)
+            .map(|comparison_time| split.run_time - comparison_time)
+    }) else {
+        text.sections[0].value.clear();
+        return;
+    };
+    text.sections[0].value = format!("{}{:.2}", if delta < 0. { "-" } else { "+" }, delta.abs());
+    text.sections[0].style.color = if delta < 0. {
+        Color::srgb(0.3, 0.9, 0.3)
+    } else {
+        Color::srgb(0.9, 0.3, 0.3)
+    };
+}
+
+/// Lists the broken entity fields of the current level so they are noticed while playtesting
+pub fn show_field_errors(
+    mut q_field_errors: Query<&mut Text, With<HudFieldErrorsMarker>>,
+    level_field_errors: Res<LevelFieldErrors>,
+) {
+    let Ok(mut text) = q_field_errors.get_single_mut() else {
+        return;
+    };
+    text.sections[0].value = level_field_errors.0.join("\n");
+}
+
 pub fn update_hud(
     mut q_hud_icons: Query<
         (
@@ -201,13 +284,10 @@ pub fn update_hud(
         ),
         With<HudIconMarker>,
     >,
-    q_player: Query<&PlayerInventory, With<PlayerMarker>>,
+    hud_items: Res<HudItems>,
     q_hud_camera: Query<(&Camera, &GlobalTransform), With<HudCameraMarker>>,
     asset_server: Res<AssetServer>,
 ) {
-    let Ok(player_inventory) = q_player.get_single() else {
-        return;
-    };
     let Ok((camera, camera_global_transform)) = q_hud_camera.get_single() else {
         return;
     };
@@ -226,27 +306,13 @@ pub fn update_hud(
     let unit_width = screen_br.x - screen_tl.x;
     let unit_height = screen_tl.y - screen_br.y;
 
-    let mut player_hud: Vec<HudIcon> = vec![HudIcon::None];
-    let mut last_ind = 1usize;
-    if player_inventory.has_wall_jump {
-        player_hud.push(HudIcon::WallJump);
-        last_ind += 1;
-    }
+    let mut items = hud_items.0.clone();
+    items.sort_by_key(|(order, _)| *order);
+    items.truncate(MAX_HUD_ICONS - 2);
 
-    for _ in 0..player_inventory.max_extra_jumps {
-        player_hud.push(HudIcon::DoubleJump);
-        last_ind += 1;
-    }
-
-    for _ in 0..player_inventory.num_keys {
-        player_hud.push(HudIcon::Key);
-        last_ind += 1;
-    }
-
-    for _ in 0..player_inventory.air_jumps {
-        player_hud.push(HudIcon::JumpToken);
-        last_ind += 1;
-    }
+    let mut player_hud: Vec<HudIcon> = vec![HudIcon::None];
+    player_hud.extend(items.iter().map(|(_, icon)| HudIcon::Item(icon)));
+    let mut last_ind = player_hud.len();
 
     if last_ind < 2 {
         last_ind = 2;
@@ -283,10 +349,7 @@ pub fn update_hud(
 
         match info.icon {
             HudIcon::None => *icon_visibility = Visibility::Hidden,
-            HudIcon::Key => *sprite = asset_server.load("key_icon.png"),
-            HudIcon::JumpToken => *sprite = asset_server.load("jump_token_icon.png"),
-            HudIcon::WallJump => *sprite = asset_server.load("wall_jump_icon.png"),
-            HudIcon::DoubleJump => *sprite = asset_server.load("double_jump_icon.png"),
+            HudIcon::Item(icon) => *sprite = asset_server.load(icon),
             HudIcon::BgLeft => *sprite = asset_server.load("inventoryleft.png"),
             HudIcon::BgCenter => *sprite = asset_server.load("inventorymiddle.png"),
             HudIcon::BgRight => *sprite = asset_server.load("inventoryright.png"),
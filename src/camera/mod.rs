@@ -1,20 +1,20 @@
 use crate::{
-    entities::{
-        goal::GoalMarker,
-        jump_token::{JumpTokenMarker, JumpTokenStatus},
+    entities::goal::GoalMarker,
+    level::{
+        fade::{fade_screen, PlayerRespawnedEvent, ScreenFade},
+        read_level_wrap, LevelWrap, WrapMode,
     },
-    level::{FromLevelSelect, LastAccessibleLevel, LEVEL_IIDS},
-    menus::{CycleCount, DeathCount},
-    player::{kill_player, loop_player, move_player, PlayerCheckpoint, PlayerMarker, PlayerStatus},
-    state::TargetLevel,
+    physics::interpolate_translation,
+    player::{abilities::HudItemSet, PlayerMarker, PlayerStatus},
 };
 use bevy::{
     prelude::*,
     render::{camera::ScalingMode, view::RenderLayers},
 };
 use bevy_ecs_ldtk::prelude::*;
-use bevy_rapier2d::prelude::Velocity;
-use hud::{show_textbox, spawn_hud, update_hud, OpenTextBoxEvent};
+use hud::{
+    show_field_errors, show_split_delta, show_textbox, spawn_hud, update_hud, OpenTextBoxEvent,
+};
 
 pub mod hud;
 
@@ -22,20 +22,15 @@ use crate::state::LevelLoadingState;
 
 const CAMERA_UNIT_WIDTH: f32 = 256. * 16. / 9.;
 
+/// Cameras, the HUD and drawing the fade to black, none of which the game needs to run without a
+/// window
 pub struct CameraManagementPlugin;
 
 impl Plugin for CameraManagementPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Startup, (setup_dim_mesh, spawn_background))
             .add_event::<OpenTextBoxEvent>()
-            .insert_resource(LdtkSettings {
-                level_background: LevelBackground::Nonexistent,
-                ..default()
-            })
-            .insert_resource(CameraPanning {
-                panning_state: CameraPanningState::PanningToGoal,
-                panning_timer: Timer::from_seconds(0.3, TimerMode::Once),
-            })
+            .add_event::<PlayerRespawnedEvent>()
             .add_systems(Update, manage_dim_mesh)
             .add_systems(OnEnter(LevelLoadingState::Loaded), spawn_background)
             .add_systems(OnExit(LevelLoadingState::Loaded), cleanup_background)
@@ -43,17 +38,20 @@ impl Plugin for CameraManagementPlugin {
                 Update,
                 (
                     pan_camera,
-                    setup_camera,
-                    attach_player_camera_to_player.after(move_player),
-                    autoscroll_camera.after(loop_player).after(move_player),
+                    setup_camera.after(read_level_wrap),
+                    attach_player_camera_to_player.after(interpolate_translation),
+                    autoscroll_camera.after(interpolate_translation),
                     loop_main_cameras,
                     spawn_hud,
-                    update_hud,
+                    update_hud.after(HudItemSet),
                     show_textbox,
-                    dim_camera
+                    show_split_delta,
+                    show_field_errors,
+                    dim_screen.after(fade_screen),
+                    follow_respawned_player
+                        .after(fade_screen)
                         .before(loop_main_cameras)
-                        .before(autoscroll_camera)
-                        .after(kill_player),
+                        .before(autoscroll_camera),
                 )
                     .run_if(in_state(LevelLoadingState::Loaded)),
             )
@@ -69,12 +67,24 @@ pub enum CameraPanningState {
     WaitingAtPlayer,
 }
 
+/// The pan to the goal and back to the player at the start of a level. The player isn't in control
+/// until it's over, and nothing pans without a [`CameraManagementPlugin`], so running the game
+/// without one has to skip it.
 #[derive(Resource)]
 pub struct CameraPanning {
     panning_timer: Timer,
     pub panning_state: CameraPanningState,
 }
 
+impl Default for CameraPanning {
+    fn default() -> Self {
+        Self {
+            panning_state: CameraPanningState::PanningToGoal,
+            panning_timer: Timer::from_seconds(0.3, TimerMode::Once),
+        }
+    }
+}
+
 fn pan_camera(
     mut camera_panning_state: ResMut<CameraPanning>,
     mut query_player_camera: Query<&mut Transform, With<PlayerCameraMarker>>,
@@ -157,7 +167,7 @@ pub const BACKGROUND_RENDER_LAYER: RenderLayers = RenderLayers::layer(1);
 pub const HUD_RENDER_LAYER: RenderLayers = RenderLayers::layer(5);
 
 #[derive(Component)]
-struct ParallaxCoefficient(f32);
+pub struct ParallaxCoefficient(pub f32);
 
 const FOREGROUND_PARALLAX_COEFFICIENT: ParallaxCoefficient = ParallaxCoefficient(1.);
 const BACKGROUND_PARALLAX_COEFFICIENT: ParallaxCoefficient = ParallaxCoefficient(0.25);
@@ -263,11 +273,20 @@ fn cleanup_background(
     }
 }
 
-fn setup_camera(mut commands: Commands, query_level: Query<&LayerMetadata, Added<LayerMetadata>>) {
+fn setup_camera(
+    mut commands: Commands,
+    query_level: Query<&LayerMetadata, Added<LayerMetadata>>,
+    query_player_camera: Query<(), With<PlayerCameraMarker>>,
+    level_wrap: Res<LevelWrap>,
+) {
+    // a level reloaded in place keeps the cameras it already has
+    if !query_player_camera.is_empty() {
+        return;
+    }
     let scaling_mode = ScalingMode::FixedHorizontal(CAMERA_UNIT_WIDTH);
     for level in query_level.iter() {
         if level.layer_instance_type == bevy_ecs_ldtk::ldtk::Type::IntGrid {
-            let level_width = level.c_wid as f32 * 16.;
+            let level_size = Vec2::new(level.c_wid as f32 * 16., level.c_hei as f32 * 16.);
             let mut player_camera = Camera2dBundle::default();
             player_camera.projection.scaling_mode = scaling_mode;
             player_camera.camera.order = PLAYER_CAMERA_ORDER;
@@ -279,48 +298,45 @@ fn setup_camera(mut commands: Commands, query_level: Query<&LayerMetadata, Added
                 // FOREGROUND_PARALLAX_COEFFICIENT,
             ));
 
-            let mut main_camera = Camera2dBundle::default();
-            main_camera.projection.scaling_mode = scaling_mode;
-            commands.spawn((
-                main_camera,
-                MainCameraMarker,
-                CameraMarker,
-                FOREGROUND_PARALLAX_COEFFICIENT,
-            ));
-
-            let mut main_camera_2 = Camera2dBundle::default();
-            main_camera_2.projection.scaling_mode = scaling_mode;
-            main_camera_2.transform.translation.x = level_width;
-            main_camera_2.camera.order = -1;
-            commands.spawn((
-                main_camera_2,
-                MainCameraMarker,
-                CameraMarker,
-                FOREGROUND_PARALLAX_COEFFICIENT,
-            ));
-
-            let mut background_camera = Camera2dBundle::default();
-            background_camera.projection.scaling_mode = scaling_mode;
-            background_camera.camera.order = -4;
-            commands.spawn((
-                background_camera,
-                BackgroundCameraMarker,
-                CameraMarker,
-                BACKGROUND_RENDER_LAYER,
-                BACKGROUND_PARALLAX_COEFFICIENT,
-            ));
-
-            let mut background_camera_2 = Camera2dBundle::default();
-            background_camera_2.projection.scaling_mode = scaling_mode;
-            background_camera_2.transform.translation.x = level_width;
-            background_camera_2.camera.order = -5;
-            commands.spawn((
-                background_camera_2,
-                BackgroundCameraMarker,
-                CameraMarker,
-                BACKGROUND_RENDER_LAYER,
-                BACKGROUND_PARALLAX_COEFFICIENT,
-            ));
+            // one copy of the main and background cameras for each way the level wraps around
+            let mut wrap_offsets = vec![Vec2::ZERO];
+            if level_wrap.0.horizontal() {
+                wrap_offsets.push(Vec2::X);
+            }
+            if level_wrap.0.vertical() {
+                wrap_offsets.push(Vec2::Y);
+            }
+            if level_wrap.0 == WrapMode::Both {
+                wrap_offsets.push(Vec2::ONE);
+            }
+            for (i, wrap_offset) in wrap_offsets.into_iter().enumerate() {
+                let offset = wrap_offset * level_size;
+
+                let mut main_camera = Camera2dBundle::default();
+                main_camera.projection.scaling_mode = scaling_mode;
+                main_camera.transform.translation.x = offset.x;
+                main_camera.transform.translation.y = offset.y;
+                main_camera.camera.order = -(i as isize);
+                commands.spawn((
+                    main_camera,
+                    MainCameraMarker,
+                    CameraMarker,
+                    FOREGROUND_PARALLAX_COEFFICIENT,
+                ));
+
+                let mut background_camera = Camera2dBundle::default();
+                background_camera.projection.scaling_mode = scaling_mode;
+                background_camera.transform.translation.x = offset.x;
+                background_camera.transform.translation.y = offset.y;
+                background_camera.camera.order = -4 - i as isize;
+                commands.spawn((
+                    background_camera,
+                    BackgroundCameraMarker,
+                    CameraMarker,
+                    BACKGROUND_RENDER_LAYER,
+                    BACKGROUND_PARALLAX_COEFFICIENT,
+                ));
+            }
 
             let mut hud_camera = Camera2dBundle::default();
             hud_camera.projection.scaling_mode = ScalingMode::WindowSize(1.);
@@ -338,18 +354,22 @@ fn setup_camera(mut commands: Commands, query_level: Query<&LayerMetadata, Added
     }
 }
 
-fn dim_camera(
-    mut camera_panning_state: ResMut<CameraPanning>,
+fn dim_screen(
+    screen_fade: Res<ScreenFade>,
     mut query_dim_sprite: Query<&mut Sprite, With<DimMeshMarker>>,
-    mut query_player: Query<
-        (
-            &mut PlayerStatus,
-            &PlayerCheckpoint,
-            &mut Transform,
-            &mut Velocity,
-        ),
-        With<PlayerMarker>,
-    >,
+) {
+    let Ok(mut dim_sprite) = query_dim_sprite.get_single_mut() else {
+        return;
+    };
+    let color_as_linear = dim_sprite.color.to_linear();
+    dim_sprite.color = Color::LinearRgba(color_as_linear.with_alpha(screen_fade.0));
+}
+
+/// Moves the cameras along with a respawned player, keeping the player camera from ending up
+/// ahead of them
+fn follow_respawned_player(
+    mut respawned_events: EventReader<PlayerRespawnedEvent>,
+    query_player: Query<&Transform, With<PlayerMarker>>,
     mut query_player_camera: Query<
         (&mut Transform, &GlobalTransform, &Camera),
         (With<PlayerCameraMarker>, Without<PlayerMarker>),
@@ -362,21 +382,9 @@ fn dim_camera(
             Without<PlayerCameraMarker>,
         ),
     >,
-    mut query_jump_tokens: Query<(&mut JumpTokenStatus, &mut Visibility), With<JumpTokenMarker>>,
-    mut next_state: ResMut<NextState<LevelLoadingState>>,
-    mut target_level: ResMut<TargetLevel>,
-    time: Res<Time>,
-    query_window: Query<&Window>,
-    mut from_level_select: ResMut<FromLevelSelect>,
-    mut last_accessible_level: ResMut<LastAccessibleLevel>,
-    mut death_count: ResMut<DeathCount>,
+    level_wrap: Res<LevelWrap>,
 ) {
-    let Ok((mut player_status, player_checkpoint, mut player_transform, mut player_velocity)) =
-        query_player.get_single_mut()
-    else {
-        return;
-    };
-    let Ok(mut dim_sprite) = query_dim_sprite.get_single_mut() else {
+    let Ok(player_transform) = query_player.get_single() else {
         return;
     };
     let Ok((mut player_camera_transform, player_camera_global_transform, player_camera)) =
@@ -384,97 +392,40 @@ fn dim_camera(
     else {
         return;
     };
-    let Ok(window) = query_window.get_single() else {
-        return;
-    };
-
-    dim_sprite.custom_size = Some(Vec2::new(window.width(), window.height()));
-    let color_as_linear = dim_sprite.color.to_linear();
-    let mut alpha = color_as_linear.alpha();
-    if player_status.level_finished || player_status.dead || player_status.exiting {
-        alpha += time.delta().as_secs_f32() * 2.;
-        if player_status.dead {
-            *player_velocity = Velocity::zero();
-        }
-        if alpha >= 1.5 {
-            alpha = 1.5;
-            if player_status.level_finished {
-                if last_accessible_level.0 < target_level.0 + 1 {
-                    last_accessible_level.0 = target_level.0 + 1;
-                }
-                if from_level_select.0 {
-                    from_level_select.0 = false;
-                    next_state.set(LevelLoadingState::LevelSelect);
-                } else {
-                    target_level.0 += 1;
-                    if target_level.0 >= LEVEL_IIDS.len() {
-                        next_state.set(LevelLoadingState::EndScreen);
-                    } else {
-                        next_state.set(LevelLoadingState::Loading);
-                        camera_panning_state.panning_state = CameraPanningState::PanningToGoal;
-                    }
-                }
-            } else if player_status.exiting {
-                if from_level_select.0 {
-                    from_level_select.0 = false;
-                    next_state.set(LevelLoadingState::LevelSelect);
-                } else {
-                    next_state.set(LevelLoadingState::MainMenu);
-                }
-            } else {
-                death_count.0 += 1;
-                player_status.dead = false;
-                for (mut token, mut visibility) in query_jump_tokens.iter_mut() {
-                    token.active = true;
-                    token.timer.reset();
-                    *visibility = Visibility::Inherited;
-                }
-                let new_translation = Vec3::new(
-                    player_checkpoint.transform.x,
-                    player_checkpoint.transform.y,
-                    0.,
-                );
-                let screen_tl = player_camera
-                    .viewport_to_world_2d(player_camera_global_transform, Vec2::new(0., 0.))
-                    .unwrap();
-                let screen_br = player_camera
-                    .viewport_to_world_2d(
-                        player_camera_global_transform,
-                        player_camera.logical_viewport_size().unwrap(),
-                    )
-                    .unwrap();
-                // the height in world units the camera can see, divided by 2
-                let low_pos = (screen_tl.y - screen_br.y) / 2.;
-
-                let delta = new_translation - player_transform.translation;
-
-                let camera_offset =
-                    player_transform.translation.x - player_camera_transform.translation.x;
-                if camera_offset < 0. {
-                    player_camera_transform.translation.x += camera_offset
-                };
-
-                player_transform.translation += delta;
-                player_camera_transform.translation += delta;
-                for (mut camera_transform, parallax_coefficient) in query_cameras.iter_mut() {
-                    camera_transform.translation += parallax_coefficient.0 * delta;
-
-                    if camera_offset < 0. {
-                        camera_transform.translation.x += parallax_coefficient.0 * camera_offset
-                    };
-                    if camera_transform.translation.y < low_pos * parallax_coefficient.0 {
-                        camera_transform.translation.y = low_pos * parallax_coefficient.0;
-                    }
-                }
+    for PlayerRespawnedEvent { from } in respawned_events.read() {
+        let screen_tl = player_camera
+            .viewport_to_world_2d(player_camera_global_transform, Vec2::new(0., 0.))
+            .unwrap();
+        let screen_br = player_camera
+            .viewport_to_world_2d(
+                player_camera_global_transform,
+                player_camera.logical_viewport_size().unwrap(),
+            )
+            .unwrap();
+        // the height in world units the camera can see, divided by 2
+        let low_pos = (screen_tl.y - screen_br.y) / 2.;
+
+        let delta = player_transform.translation - *from;
+
+        let camera_offset = from.x - player_camera_transform.translation.x;
+        if camera_offset < 0. {
+            player_camera_transform.translation.x += camera_offset
+        };
+
+        player_camera_transform.translation += delta;
+        for (mut camera_transform, parallax_coefficient) in query_cameras.iter_mut() {
+            camera_transform.translation += parallax_coefficient.0 * delta;
+
+            if camera_offset < 0. {
+                camera_transform.translation.x += parallax_coefficient.0 * camera_offset
+            };
+            if camera_transform.translation.y < low_pos * parallax_coefficient.0
+                && !level_wrap.0.vertical()
+            {
+                camera_transform.translation.y = low_pos * parallax_coefficient.0;
             }
         }
-    } else {
-        alpha -= time.delta().as_secs_f32() * 2.;
-        if alpha < 0. {
-            alpha = 0.;
-        }
     }
-    dim_sprite.color = Color::LinearRgba(color_as_linear.with_alpha(alpha));
 }
 
 // TODO: make this use delta time!
@@ -501,6 +452,8 @@ fn attach_player_camera_to_player(
             Without<PlayerCameraMarker>,
         ),
     >,
+    query_level: Query<&LayerMetadata>,
+    level_wrap: Res<LevelWrap>,
     time: Res<Time>,
 ) {
     // if camera_panning_state.panning_state != CameraPanningState::WaitingAtPlayer && camera_panning_state.panning_state != CameraPanningState::PanningToPlayer {
@@ -548,10 +501,18 @@ fn attach_player_camera_to_player(
         .unwrap();
     // the height in world units the camera can see, divided by 2
     let low_pos = (screen_tl.y - screen_br.y) / 2.;
-    let delta = (target.y) - player_camera_transform.translation.y;
+    // levels that scroll vertically autoscroll up instead once the intro pan is over
+    let delta = if level_wrap.0.scroll_axis() == 1
+        && camera_panning_state.panning_state == CameraPanningState::WaitingAtPlayer
+    {
+        0.
+    } else {
+        target.y - player_camera_transform.translation.y
+    };
     player_camera_transform.translation.y += delta / motion_factor * delta_time_coeff;
     let mut is_at_low = false;
-    if player_camera_transform.translation.y < low_pos {
+    // levels that wrap vertically have no floor to stop at
+    if player_camera_transform.translation.y < low_pos && !level_wrap.0.vertical() {
         player_camera_transform.translation.y = low_pos;
         is_at_low = true;
     }
@@ -562,31 +523,64 @@ fn attach_player_camera_to_player(
             main_camera_transform.translation.y = low_pos * parallax_coefficient.0;
         }
     }
+
+    // levels that wrap horizontally autoscroll instead, and the intro pan moves the camera
+    // horizontally by itself
+    if level_wrap.0.horizontal()
+        || camera_panning_state.panning_state != CameraPanningState::WaitingAtPlayer
+    {
+        return;
+    }
+    let Some(level_width) = query_level
+        .iter()
+        .find(|level| level.layer_instance_type == bevy_ecs_ldtk::ldtk::Type::IntGrid)
+        .map(|level| level.c_wid as f32 * 16.)
+    else {
+        return;
+    };
+    // the width in world units the camera can see, divided by 2
+    let half_width = (screen_br.x - screen_tl.x) / 2.;
+    let target_x = if level_width > 2. * half_width {
+        target.x.clamp(half_width, level_width - half_width)
+    } else {
+        level_width / 2.
+    };
+    let delta = target_x - player_camera_transform.translation.x;
+    player_camera_transform.translation.x += delta / motion_factor * delta_time_coeff;
+    for (mut main_camera_transform, parallax_coefficient) in query_main_camera.iter_mut() {
+        main_camera_transform.translation.x +=
+            parallax_coefficient.0 * delta / motion_factor * delta_time_coeff;
+    }
 }
 
 fn loop_main_cameras(
     mut query_main_cameras: Query<&mut Transform, With<CameraMarker>>,
     query_level: Query<&LayerMetadata>,
-    mut cycle_count: ResMut<CycleCount>,
-    camera_panning_state: Res<CameraPanning>,
+    level_wrap: Res<LevelWrap>,
 ) {
     let mut level_width = 1000. * 16.;
+    let mut level_height = 1000. * 16.;
     for level in query_level.iter() {
         if level.layer_instance_type == bevy_ecs_ldtk::ldtk::Type::IntGrid {
             level_width = level.c_wid as f32 * 16.;
+            level_height = level.c_hei as f32 * 16.;
         }
     }
     for mut camera_transform in query_main_cameras.iter_mut() {
-        if camera_transform.translation.x > 3. * level_width / 2. {
-            camera_transform.translation.x -= 2. * level_width;
-            if camera_panning_state.panning_state == CameraPanningState::WaitingAtPlayer {
-                cycle_count.0 += 1;
+        if level_wrap.0.horizontal() {
+            if camera_transform.translation.x > 3. * level_width / 2. {
+                camera_transform.translation.x -= 2. * level_width;
+            }
+            if camera_transform.translation.x < -0.5 * level_width {
+                camera_transform.translation.x += 2. * level_width;
             }
         }
-        if camera_transform.translation.x < -0.5 * level_width {
-            camera_transform.translation.x += 2. * level_width;
-            if camera_panning_state.panning_state == CameraPanningState::WaitingAtPlayer {
-                cycle_count.0 -= 1;
+        if level_wrap.0.vertical() {
+            if camera_transform.translation.y > 3. * level_height / 2. {
+                camera_transform.translation.y -= 2. * level_height;
+            }
+            if camera_transform.translation.y < -0.5 * level_height {
+                camera_transform.translation.y += 2. * level_height;
             }
         }
     }
@@ -611,19 +605,22 @@ fn autoscroll_camera(
         ),
     >,
     query_level: Query<&LayerMetadata>,
+    level_wrap: Res<LevelWrap>,
 ) {
+    // right, or up in levels that scroll vertically
+    let axis = level_wrap.0.scroll_axis();
     if let Ok(player_transform) = query_player.get_single() {
-        let mut level_width = 1000. * 16.;
+        let mut level_length = 1000. * 16.;
         for level in query_level.iter() {
             if level.layer_instance_type == bevy_ecs_ldtk::ldtk::Type::IntGrid {
-                level_width = level.c_wid as f32 * 16.;
+                level_length = [level.c_wid, level.c_hei][axis] as f32 * 16.;
             }
         }
 
         if let Ok(mut player_camera_transform) = query_player_camera.get_single_mut() {
-            let new_transform = player_camera_transform.translation.x/*  % level_width */;
+            let new_transform = player_camera_transform.translation[axis];
             let new_player_transform =
-                ((player_transform.translation.x % level_width) + level_width) % level_width;
+                ((player_transform.translation[axis] % level_length) + level_length) % level_length;
             let delta = new_player_transform - new_transform;
             if delta <= 0. {
                 return;
@@ -631,11 +628,11 @@ fn autoscroll_camera(
             // NOTE: if the delta is greater than 0, that means the player's world transform is
             // greater than the camera's, which is in the center of the screen. So the camera will
             // only scroll if the player is past the halfway point
-            let modded_delta = ((delta % level_width) + level_width) % level_width;
+            let modded_delta = ((delta % level_length) + level_length) % level_length;
 
-            player_camera_transform.translation.x += modded_delta;
+            player_camera_transform.translation[axis] += modded_delta;
             for (mut camera_transform, parallax_coefficient) in query_main_cameras.iter_mut() {
-                camera_transform.translation.x += modded_delta
+                camera_transform.translation[axis] += modded_delta
                     * if let Some(c) = parallax_coefficient {
                         c.0
                     } else {
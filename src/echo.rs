@@ -0,0 +1,270 @@
+use std::mem;
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    camera::{CameraPanning, CameraPanningState, PLAYER_RENDER_LAYER},
+    entities::{lever::check_lever_interacting, INTERACT_KEYCODE},
+    input::GameplayInput,
+    level::KillPlayerMarker,
+    menus::CycleCount,
+    physics::TranslationInterpolation,
+    player::{PlayerMarker, PlayerStatus},
+    state::LevelLoadingState,
+};
+
+/// LDtk level field picking what the echo of a level does, an enum with the values `Hazard` and
+/// `Helper`. Levels without it have no echo.
+const ECHO_FIELD: &str = "echo";
+
+/// Ticks an echo takes to fade in before it can touch anything, since it starts out roughly where
+/// the player is when the level wraps around
+const ECHO_FADE_IN_TICKS: usize = 60;
+
+const ECHO_ALPHA: f32 = 0.6;
+
+/// How far from a lever an echo can pull it, matching the player's collider touching the lever's
+/// sensor
+const ECHO_LEVER_REACH: Vec2 = Vec2::new(16., 13.);
+
+pub struct EchoManagementPlugin;
+
+impl Plugin for EchoManagementPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LevelEcho>()
+            .init_resource::<EchoRecorder>()
+            .add_systems(Update, (read_level_echo, reset_echo_recorder))
+            .add_systems(
+                FixedUpdate,
+                (
+                    advance_echo
+                        .before(check_lever_interacting)
+                        .before(PhysicsSet::SyncBackend),
+                    record_echo_frame.after(PhysicsSet::Writeback),
+                )
+                    .run_if(in_state(LevelLoadingState::Loaded))
+                    .run_if(level_has_echo),
+            );
+    }
+}
+
+/// What the echo of a level does when it touches the player
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EchoMode {
+    /// Kills the player on contact
+    Hazard,
+    /// A solid body the player can stand on
+    Helper,
+}
+
+/// The echo mode of the current level, read from its [`ECHO_FIELD`]
+#[derive(Resource, Default)]
+pub struct LevelEcho(pub Option<EchoMode>);
+
+/// Where the player was and whether they interacted during one fixed tick
+#[derive(Debug, Clone, Copy)]
+pub struct EchoFrame {
+    pub position: Vec2,
+    pub sprite_index: usize,
+    pub flip_x: bool,
+    pub interacted: bool,
+}
+
+/// The path of the player through the current cycle of the level
+#[derive(Resource, Default)]
+pub struct EchoRecorder {
+    pub frames: Vec<EchoFrame>,
+    /// Value of [`CycleCount`] the frames were recorded in
+    pub cycle: usize,
+}
+
+/// Replays the player's path through the previous cycle of the level, one frame per fixed tick
+#[derive(Component)]
+pub struct Echo {
+    frames: Vec<EchoFrame>,
+    /// Index of the frame the echo is at
+    tick: usize,
+}
+
+impl Echo {
+    /// Whether the echo is pulling a lever at `lever_position` this tick
+    pub fn pulls_lever(&self, echo_position: Vec2, lever_position: Vec2) -> bool {
+        self.tick >= ECHO_FADE_IN_TICKS
+            && self
+                .frames
+                .get(self.tick)
+                .is_some_and(|frame| frame.interacted)
+            && (echo_position - lever_position)
+                .abs()
+                .cmplt(ECHO_LEVER_REACH)
+                .all()
+    }
+}
+
+fn level_has_echo(level_echo: Res<LevelEcho>) -> bool {
+    level_echo.0.is_some()
+}
+
+fn read_level_echo(
+    query_level: Query<&LevelIid, Added<LevelIid>>,
+    query_ldtk_world: Query<&Handle<LdtkProject>>,
+    ldtk_projects: Res<Assets<LdtkProject>>,
+    mut level_echo: ResMut<LevelEcho>,
+) {
+    let Ok(level_iid) = query_level.get_single() else {
+        return;
+    };
+    let mode = query_ldtk_world
+        .get_single()
+        .ok()
+        .and_then(|handle| ldtk_projects.get(handle))
+        .and_then(|project| project.get_raw_level_by_iid(level_iid.get()))
+        .and_then(|level| level.get_maybe_enum_field(ECHO_FIELD).ok())
+        .and_then(Option::as_ref);
+    level_echo.0 = match mode.map(String::as_str) {
+        Some("Hazard") => Some(EchoMode::Hazard),
+        Some("Helper") => Some(EchoMode::Helper),
+        Some(mode) => {
+            warn!("unknown echo mode {mode}, the level will have no echo");
+            None
+        }
+        None => None,
+    };
+}
+
+fn reset_echo_recorder(
+    query_player: Query<(), Added<PlayerMarker>>,
+    cycle_count: Res<CycleCount>,
+    mut echo_recorder: ResMut<EchoRecorder>,
+) {
+    if query_player.is_empty() {
+        return;
+    }
+    *echo_recorder = EchoRecorder {
+        frames: Vec::new(),
+        cycle: cycle_count.0,
+    };
+}
+
+/// Records the player's path, and hands it to a new echo every time the level wraps around
+fn record_echo_frame(
+    mut commands: Commands,
+    query_player: Query<
+        (
+            &Parent,
+            &Transform,
+            &Sprite,
+            &Handle<Image>,
+            &TextureAtlas,
+            &PlayerStatus,
+        ),
+        With<PlayerMarker>,
+    >,
+    query_echoes: Query<Entity, With<Echo>>,
+    camera_panning: Res<CameraPanning>,
+    keys: Res<GameplayInput>,
+    cycle_count: Res<CycleCount>,
+    level_echo: Res<LevelEcho>,
+    mut echo_recorder: ResMut<EchoRecorder>,
+) {
+    let Ok((parent, transform, sprite, texture, atlas, player_status)) = query_player.get_single()
+    else {
+        return;
+    };
+    if camera_panning.panning_state != CameraPanningState::WaitingAtPlayer
+        || player_status.level_finished
+    {
+        return;
+    }
+    echo_recorder.frames.push(EchoFrame {
+        position: transform.translation.xy(),
+        sprite_index: atlas.index,
+        flip_x: sprite.flip_x,
+        interacted: keys.just_pressed(INTERACT_KEYCODE),
+    });
+    if echo_recorder.cycle == cycle_count.0 {
+        return;
+    }
+    echo_recorder.cycle = cycle_count.0;
+    let frames = mem::take(&mut echo_recorder.frames);
+    for echo in query_echoes.iter() {
+        commands.entity(echo).despawn_recursive();
+    }
+    let Some(mode) = level_echo.0 else {
+        return;
+    };
+    let translation = frames[0].position.extend(transform.translation.z - 0.1);
+    let color = match mode {
+        EchoMode::Hazard => Color::srgba(1., 0.35, 0.35, 0.),
+        EchoMode::Helper => Color::srgba(0.45, 0.75, 1., 0.),
+    };
+    commands.entity(parent.get()).with_children(|parent| {
+        let mut echo = parent.spawn((
+            SpriteBundle {
+                sprite: Sprite { color, ..default() },
+                texture: texture.clone(),
+                transform: Transform::from_translation(translation),
+                ..default()
+            },
+            TextureAtlas {
+                layout: atlas.layout.clone(),
+                index: frames[0].sprite_index,
+            },
+            PLAYER_RENDER_LAYER,
+            TranslationInterpolation::new(translation),
+            Echo { frames, tick: 0 },
+        ));
+        if mode == EchoMode::Helper {
+            echo.insert(RigidBody::KinematicPositionBased);
+        }
+    });
+}
+
+/// Moves echoes along their path, giving them a collider once they have faded in
+fn advance_echo(
+    mut commands: Commands,
+    mut query_echoes: Query<(
+        Entity,
+        &mut Echo,
+        &mut Transform,
+        &mut Sprite,
+        &mut TextureAtlas,
+    )>,
+    level_echo: Res<LevelEcho>,
+) {
+    for (entity, mut echo, mut transform, mut sprite, mut atlas) in query_echoes.iter_mut() {
+        echo.tick += 1;
+        let Some(frame) = echo.frames.get(echo.tick).copied() else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+        transform.translation = frame.position.extend(transform.translation.z);
+        sprite.flip_x = frame.flip_x;
+        atlas.index = frame.sprite_index;
+        sprite
+            .color
+            .set_alpha(ECHO_ALPHA * (echo.tick as f32 / ECHO_FADE_IN_TICKS as f32).min(1.));
+
+        if echo.tick != ECHO_FADE_IN_TICKS {
+            continue;
+        }
+        // same shape and offset as the player's collider
+        let collider = (
+            Collider::round_cuboid(6., 3., 2.),
+            TransformBundle::from_transform(Transform::from_xyz(0., -2., 0.)),
+        );
+        commands
+            .entity(entity)
+            .with_children(|parent| match level_echo.0 {
+                Some(EchoMode::Hazard) => {
+                    parent.spawn((collider, Sensor, KillPlayerMarker));
+                }
+                Some(EchoMode::Helper) => {
+                    parent.spawn(collider);
+                }
+                None => {}
+            });
+    }
+}
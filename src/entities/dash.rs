@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::{
+    player::{abilities::dash::Dash, animation::AnimationTimer, PlayerMarker},
+    sound_effects::SoundEffectType,
+};
+
+use super::trigger::{TriggerArea, TriggerEffect, TriggerEvent, TriggerKind};
+
+#[derive(Component, Debug)]
+pub struct DashMarker;
+
+#[derive(Bundle, LdtkEntity)]
+pub struct DashBundle {
+    #[sprite_sheet_bundle("../assets/spritesheets/dash.png", 16, 16, 4, 1, 0, 0, 0)]
+    sprite_sheet_bundle: LdtkSpriteSheetBundle,
+    dash_marker: DashMarker,
+    trigger: TriggerArea,
+    animation_timer: AnimationTimer,
+}
+
+impl Default for DashBundle {
+    fn default() -> Self {
+        Self {
+            sprite_sheet_bundle: LdtkSpriteSheetBundle::default(),
+            dash_marker: DashMarker,
+            trigger: TriggerArea::new(Vec2::splat(7.))
+                .on_enter(TriggerEffect::pickup(SoundEffectType::BigPowerup).set_checkpoint()),
+            animation_timer: AnimationTimer(Timer::new(
+                Duration::from_millis(300),
+                TimerMode::Repeating,
+            )),
+        }
+    }
+}
+
+pub fn animate_dash(
+    time: Res<Time>,
+    mut query: Query<(&mut AnimationTimer, &mut TextureAtlas), With<DashMarker>>,
+) {
+    for (mut timer, mut atlas) in query.iter_mut() {
+        timer.tick(time.delta());
+        if timer.0.finished() {
+            atlas.index = (atlas.index + 1) % 4;
+        }
+    }
+}
+
+pub fn collect_dash(
+    mut commands: Commands,
+    mut trigger_events: EventReader<TriggerEvent>,
+    query_pickups: Query<(), With<DashMarker>>,
+    query_player: Query<Entity, (With<PlayerMarker>, Without<Dash>)>,
+) {
+    let Ok(player) = query_player.get_single() else {
+        return;
+    };
+
+    for event in trigger_events.read() {
+        if event.kind == TriggerKind::Enter && query_pickups.contains(event.trigger) {
+            commands.entity(player).insert(Dash::default());
+        }
+    }
+}
@@ -5,6 +5,7 @@ use bevy_ecs_ldtk::prelude::*;
 use bevy_rapier2d::prelude::*;
 
 use crate::{
+    input::GameplayInput,
     player::{
         animation::AnimationTimer, PlayerColliderMarker, PlayerInventory, PlayerMarker,
         SetCheckpointEvent,
@@ -119,7 +120,7 @@ pub fn check_door_interacting(
     mut query_player: Query<&mut PlayerInventory, With<PlayerMarker>>,
     query_player_collider: Query<Entity, With<PlayerColliderMarker>>,
     mut query_door_state: Query<(Entity, &mut DoorAnimationState, &mut DoorState)>,
-    keys: Res<ButtonInput<KeyCode>>,
+    keys: Res<GameplayInput>,
     mut checkpoint_event_writer: EventWriter<SetCheckpointEvent>,
     mut sound_effect_event_writer: EventWriter<SoundEffectEvent>,
 ) {
@@ -134,8 +135,11 @@ pub fn check_door_interacting(
     }
 
     for (door, door_collider) in query_doors.iter_mut() {
-        let (door_entity, mut animation_state, mut door_state) =
-            query_door_state.get_mut(door.get()).unwrap();
+        let Ok((door_entity, mut animation_state, mut door_state)) =
+            query_door_state.get_mut(door.get())
+        else {
+            continue;
+        };
 
         if rapier_context.intersection_pair(player_collider, door_collider) == Some(true) {
             if inventory.num_keys >= 1 {
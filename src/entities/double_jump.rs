@@ -2,27 +2,23 @@ use std::time::Duration;
 
 use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
-use bevy_rapier2d::prelude::*;
 
 use crate::{
-    player::{
-        animation::AnimationTimer, PlayerColliderMarker, PlayerInventory, PlayerMarker,
-        SetCheckpointEvent,
-    },
-    sound_effects::{SoundEffectEvent, SoundEffectType},
+    player::{abilities::double_jump::DoubleJump, animation::AnimationTimer, PlayerMarker},
+    sound_effects::SoundEffectType,
 };
 
-#[derive(Component, Debug)]
-pub struct DoubleJumpMarker;
+use super::trigger::{TriggerArea, TriggerEffect, TriggerEvent, TriggerKind};
 
 #[derive(Component, Debug)]
-pub struct DoubleJumpSensorMarker;
+pub struct DoubleJumpMarker;
 
 #[derive(Bundle, LdtkEntity)]
 pub struct DoubleJumpBundle {
     #[sprite_sheet_bundle("../assets/spritesheets/doublejump.png", 16, 16, 4, 1, 0, 0, 0)]
     sprite_sheet_bundle: LdtkSpriteSheetBundle,
     double_jump_marker: DoubleJumpMarker,
+    trigger: TriggerArea,
     animation_timer: AnimationTimer,
 }
 
@@ -31,6 +27,8 @@ impl Default for DoubleJumpBundle {
         Self {
             sprite_sheet_bundle: LdtkSpriteSheetBundle::default(),
             double_jump_marker: DoubleJumpMarker,
+            trigger: TriggerArea::new(Vec2::splat(7.))
+                .on_enter(TriggerEffect::pickup(SoundEffectType::BigPowerup).set_checkpoint()),
             animation_timer: AnimationTimer(Timer::new(
                 Duration::from_millis(300),
                 TimerMode::Repeating,
@@ -51,48 +49,32 @@ pub fn animate_double_jump(
     }
 }
 
-pub fn add_double_jump_sensor(
-    mut commands: Commands,
-    query_jump_token: Query<Entity, Added<DoubleJumpMarker>>,
-) {
-    for token in query_jump_token.iter() {
-        commands.entity(token).with_children(|parent| {
-            parent.spawn((
-                Collider::cuboid(7., 7.),
-                Sensor,
-                ActiveEvents::COLLISION_EVENTS,
-                DoubleJumpSensorMarker,
-                TransformBundle::from_transform(Transform::from_xyz(0., 0., 0.)),
-            ));
-        });
-    }
-}
-
-pub fn check_double_jump_acquire(
+pub fn collect_double_jump(
     mut commands: Commands,
-    rapier_context: Res<RapierContext>,
-    mut q_double_jump_sensor: Query<(&mut Parent, Entity), With<DoubleJumpSensorMarker>>,
-    mut query_player: Query<&mut PlayerInventory, With<PlayerMarker>>,
-    query_player_collider: Query<Entity, With<PlayerColliderMarker>>,
-    mut q_double_jump: Query<Entity>,
-    mut checkpoint_event_writer: EventWriter<SetCheckpointEvent>,
-    mut sound_effect_event_writer: EventWriter<SoundEffectEvent>,
+    mut trigger_events: EventReader<TriggerEvent>,
+    query_pickups: Query<(), With<DoubleJumpMarker>>,
+    mut query_player: Query<(Entity, Option<&mut DoubleJump>), With<PlayerMarker>>,
 ) {
-    let Ok(mut inventory) = query_player.get_single_mut() else {
-        return;
-    };
-    let Ok(player_collider) = query_player_collider.get_single() else {
+    let Ok((player, mut double_jump)) = query_player.get_single_mut() else {
         return;
     };
 
-    for (double_jump, dj_sensor_entity) in q_double_jump_sensor.iter_mut() {
-        let dj_entity = q_double_jump.get_mut(double_jump.get()).unwrap();
-        if rapier_context.intersection_pair(player_collider, dj_sensor_entity) == Some(true) {
-            sound_effect_event_writer.send(SoundEffectEvent(SoundEffectType::BigPowerup));
-            inventory.max_extra_jumps += 1;
-            // println!("PLAYER EXTRA JUMPS {:?}", inventory.max_extra_jumps);
-            commands.entity(dj_entity).despawn_recursive();
-            checkpoint_event_writer.send(SetCheckpointEvent);
+    let mut collected = 0;
+    for event in trigger_events.read() {
+        if event.kind == TriggerKind::Enter && query_pickups.contains(event.trigger) {
+            collected += 1;
+        }
+    }
+    if collected == 0 {
+        return;
+    }
+    match double_jump.as_mut() {
+        Some(double_jump) => double_jump.max += collected,
+        None => {
+            commands.entity(player).insert(DoubleJump {
+                max: collected,
+                remaining: 0,
+            });
         }
     }
 }
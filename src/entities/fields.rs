@@ -0,0 +1,236 @@
+use std::ops::RangeInclusive;
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::{ldtk::ldtk_fields::LdtkFieldsError, prelude::*};
+use thiserror::Error;
+
+/// A field of an LDtk entity that couldn't be used as is
+#[derive(Debug, Error)]
+pub enum EntityFieldError {
+    #[error(transparent)]
+    Unreadable(#[from] LdtkFieldsError),
+    #[error("{field} is {value}, expected {} to {}", .range.start(), .range.end())]
+    OutOfRange {
+        field: &'static str,
+        value: i32,
+        range: RangeInclusive<i32>,
+    },
+}
+
+/// The fields of an LDtk entity that fell back to a default value, reported by
+/// [`report_entity_field_errors`]
+#[derive(Component, Debug, Default)]
+pub struct EntityFieldErrors {
+    identifier: String,
+    grid: IVec2,
+    errors: Vec<EntityFieldError>,
+}
+
+/// Reads the fields of an LDtk entity, using fallbacks for broken fields instead of panicking
+pub struct EntityFields<'a> {
+    entity_instance: &'a EntityInstance,
+    errors: Vec<EntityFieldError>,
+}
+
+impl<'a> EntityFields<'a> {
+    pub fn new(entity_instance: &'a EntityInstance) -> Self {
+        Self {
+            entity_instance,
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn int(&mut self, field: &'static str, fallback: i32) -> i32 {
+        match self.entity_instance.get_int_field(field) {
+            Ok(value) => *value,
+            Err(e) => {
+                self.errors.push(e.into());
+                fallback
+            }
+        }
+    }
+
+    /// Reads an int field, recording values outside of `range` as errors but still returning them
+    pub fn int_in_range(
+        &mut self,
+        field: &'static str,
+        range: RangeInclusive<i32>,
+        fallback: i32,
+    ) -> i32 {
+        let entity_instance = self.entity_instance;
+        let Ok(value) = entity_instance.get_int_field(field) else {
+            return self.int(field, fallback);
+        };
+        if !range.contains(value) {
+            self.errors.push(EntityFieldError::OutOfRange {
+                field,
+                value: *value,
+                range,
+            });
+        }
+        *value
+    }
+
+    pub fn bool(&mut self, field: &'static str, fallback: bool) -> bool {
+        match self.entity_instance.get_bool_field(field) {
+            Ok(value) => *value,
+            Err(e) => {
+                self.errors.push(e.into());
+                fallback
+            }
+        }
+    }
+
+    pub fn string(&mut self, field: &'static str, fallback: &str) -> String {
+        match self.entity_instance.get_string_field(field) {
+            Ok(value) => value.clone(),
+            Err(e) => {
+                self.errors.push(e.into());
+                fallback.to_string()
+            }
+        }
+    }
+
+    pub fn finish(self) -> EntityFieldErrors {
+        EntityFieldErrors {
+            identifier: self.entity_instance.identifier.clone(),
+            grid: self.entity_instance.grid,
+            errors: self.errors,
+        }
+    }
+}
+
+/// Every broken entity field of the current level, one line per field
+#[derive(Resource, Default)]
+pub struct LevelFieldErrors(pub Vec<String>);
+
+/// Logs the broken fields of newly spawned entities along with the level they are in
+pub fn report_entity_field_errors(
+    query_entities: Query<(&EntityFieldErrors, &Parent), Added<EntityFieldErrors>>,
+    query_new_levels: Query<(), Added<LevelIid>>,
+    query_parents: Query<&Parent>,
+    query_levels: Query<&LevelIid>,
+    query_ldtk_world: Query<&Handle<LdtkProject>>,
+    ldtk_projects: Res<Assets<LdtkProject>>,
+    mut level_field_errors: ResMut<LevelFieldErrors>,
+) {
+    if !query_new_levels.is_empty() {
+        level_field_errors.0.clear();
+    }
+    let ldtk_project = query_ldtk_world
+        .get_single()
+        .ok()
+        .and_then(|handle| ldtk_projects.get(handle));
+
+    for (field_errors, layer) in query_entities.iter() {
+        if field_errors.errors.is_empty() {
+            continue;
+        }
+        let level = query_parents
+            .get(layer.get())
+            .and_then(|level| query_levels.get(level.get()))
+            .ok()
+            .and_then(|level_iid| ldtk_project?.get_raw_level_by_iid(level_iid.get()))
+            .map_or("unknown level", |level| level.identifier.as_str());
+        for e in field_errors.errors.iter() {
+            let message = format!(
+                "{level}: {} at {}: {e}",
+                field_errors.identifier, field_errors.grid
+            );
+            error!("{message}");
+            level_field_errors.0.push(message);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_ecs_ldtk::ldtk::{FieldInstance, FieldValue};
+
+    fn entity_instance(fields: Vec<(&str, FieldValue)>) -> EntityInstance {
+        EntityInstance {
+            identifier: "Lever".into(),
+            grid: IVec2::new(3, 7),
+            field_instances: fields
+                .into_iter()
+                .map(|(identifier, value)| FieldInstance {
+                    identifier: identifier.into(),
+                    tile: None,
+                    field_instance_type: String::new(),
+                    value,
+                    def_uid: 0,
+                    real_editor_values: Vec::new(),
+                })
+                .collect(),
+            ..default()
+        }
+    }
+
+    #[test]
+    fn reads_valid_fields() {
+        let entity_instance = entity_instance(vec![
+            ("id", FieldValue::Int(Some(2))),
+            ("on", FieldValue::Bool(true)),
+            ("text", FieldValue::String(Some("hi".into()))),
+        ]);
+        let mut fields = EntityFields::new(&entity_instance);
+        assert_eq!(fields.int_in_range("id", 1..=4, 0), 2);
+        assert!(fields.bool("on", false));
+        assert_eq!(fields.string("text", ""), "hi");
+        assert!(fields.finish().errors.is_empty());
+    }
+
+    #[test]
+    fn out_of_range_ints_are_kept_but_reported() {
+        let entity_instance = entity_instance(vec![("id", FieldValue::Int(Some(9)))]);
+        let mut fields = EntityFields::new(&entity_instance);
+        assert_eq!(fields.int_in_range("id", 1..=4, 0), 9);
+        let errors = fields.finish().errors;
+        assert!(matches!(
+            errors[..],
+            [EntityFieldError::OutOfRange {
+                field: "id",
+                value: 9,
+                ..
+            }]
+        ));
+        assert_eq!(errors[0].to_string(), "id is 9, expected 1 to 4");
+    }
+
+    #[test]
+    fn missing_and_mistyped_fields_fall_back() {
+        let entity_instance = entity_instance(vec![
+            ("id", FieldValue::Bool(true)),
+            ("on", FieldValue::Int(Some(1))),
+            ("text", FieldValue::String(None)),
+        ]);
+        let mut fields = EntityFields::new(&entity_instance);
+        assert_eq!(fields.int_in_range("id", 1..=4, 0), 0);
+        assert!(fields.bool("on", true));
+        assert_eq!(fields.string("text", "fallback"), "fallback");
+        assert_eq!(fields.int("missing", 5), 5);
+
+        let errors = fields.finish().errors;
+        assert!(matches!(
+            &errors[..],
+            [
+                EntityFieldError::Unreadable(LdtkFieldsError::WrongFieldType { .. }),
+                EntityFieldError::Unreadable(LdtkFieldsError::WrongFieldType { .. }),
+                EntityFieldError::Unreadable(LdtkFieldsError::UnexpectedNull { .. }),
+                EntityFieldError::Unreadable(LdtkFieldsError::FieldNotFound { .. }),
+            ]
+        ));
+    }
+
+    #[test]
+    fn finish_keeps_where_the_entity_is() {
+        let entity_instance = entity_instance(vec![]);
+        let mut fields = EntityFields::new(&entity_instance);
+        fields.bool("on", false);
+        let field_errors = fields.finish();
+        assert_eq!(field_errors.identifier, "Lever");
+        assert_eq!(field_errors.grid, IVec2::new(3, 7));
+        assert_eq!(field_errors.errors.len(), 1);
+    }
+}
@@ -1,20 +1,19 @@
 use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
-use bevy_rapier2d::prelude::*;
 
-use crate::player::{PlayerColliderMarker, PlayerMarker, PlayerStatus};
+use crate::player::{LevelCompletedEvent, PlayerMarker, PlayerStatus};
 
-#[derive(Component, Debug)]
-pub struct GoalMarker;
+use super::trigger::{TriggerArea, TriggerEffect, TriggerEvent, TriggerKind};
 
 #[derive(Component, Debug)]
-pub struct GoalSensorMarker;
+pub struct GoalMarker;
 
 #[derive(Bundle, LdtkEntity)]
 pub struct GoalBundle {
     #[sprite_sheet_bundle("../assets/goal.png", 48, 32, 1, 1, 0, 0, 1)]
     sprite_sheet_bundle: LdtkSpriteSheetBundle,
     goal_marker: GoalMarker,
+    trigger: TriggerArea,
 }
 
 impl Default for GoalBundle {
@@ -22,41 +21,27 @@ impl Default for GoalBundle {
         Self {
             sprite_sheet_bundle: LdtkSpriteSheetBundle::default(),
             goal_marker: GoalMarker,
+            trigger: TriggerArea::new(Vec2::new(24., 16.)).on_enter(TriggerEffect::default()),
         }
     }
 }
 
-pub fn add_goal_sensor(mut commands: Commands, query_goals: Query<Entity, Added<GoalMarker>>) {
-    for goal in query_goals.iter() {
-        commands.entity(goal).with_children(|parent| {
-            parent.spawn((
-                Collider::cuboid(24., 16.),
-                Sensor,
-                ActiveEvents::COLLISION_EVENTS,
-                TransformBundle::from_transform(Transform::from_xyz(0., 0., 0.)),
-                GoalSensorMarker,
-            ));
-        });
-    }
-}
-
 pub fn check_goal_reached(
-    rapier_context: Res<RapierContext>,
-    query_goal_sensor: Query<Entity, With<GoalSensorMarker>>,
+    mut trigger_events: EventReader<TriggerEvent>,
+    query_goals: Query<(), With<GoalMarker>>,
     mut query_player: Query<&mut PlayerStatus, With<PlayerMarker>>,
-    query_player_collider: Query<Entity, With<PlayerColliderMarker>>,
+    mut level_completed_event_writer: EventWriter<LevelCompletedEvent>,
 ) {
     let Ok(mut player_status) = query_player.get_single_mut() else {
         return;
     };
-    let Ok(player_collider) = query_player_collider.get_single() else {
-        return;
-    };
-    let Ok(goal_sensor) = query_goal_sensor.get_single() else {
-        return;
-    };
 
-    if rapier_context.intersection_pair(player_collider, goal_sensor) == Some(true) {
-        player_status.level_finished = true;
+    for event in trigger_events.read() {
+        if event.kind == TriggerKind::Enter && query_goals.contains(event.trigger) {
+            if !player_status.level_finished {
+                level_completed_event_writer.send(LevelCompletedEvent);
+            }
+            player_status.level_finished = true;
+        }
     }
 }
@@ -2,32 +2,25 @@ use std::time::Duration;
 
 use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
-use bevy_rapier2d::prelude::*;
 use rand::prelude::*;
 
 use crate::{
-    player::{animation::AnimationTimer, PlayerColliderMarker, PlayerInventory, PlayerMarker},
-    sound_effects::{SoundEffectEvent, SoundEffectType},
+    player::{abilities::air_jump_token::AirJumpTokens, animation::AnimationTimer, PlayerMarker},
+    replay::GameRng,
+    sound_effects::SoundEffectType,
 };
 
-#[derive(Component, Debug)]
-pub struct JumpTokenMarker;
+use super::trigger::{TriggerArea, TriggerEffect, TriggerEvent, TriggerKind};
 
 #[derive(Component, Debug)]
-pub struct JumpTokenSensorMarker;
-
-#[derive(Component, Debug)]
-pub struct JumpTokenStatus {
-    pub active: bool,
-    pub timer: Timer,
-}
+pub struct JumpTokenMarker;
 
 #[derive(Bundle, LdtkEntity)]
 pub struct JumpTokenBundle {
     #[sprite_sheet_bundle("../assets/spritesheets/jumptoken.png", 16, 16, 4, 1, 0, 0, 0)]
     sprite_sheet_bundle: LdtkSpriteSheetBundle,
     token_marker: JumpTokenMarker,
-    token_status: JumpTokenStatus,
+    trigger: TriggerArea,
     animation_timer: AnimationTimer,
 }
 
@@ -36,10 +29,9 @@ impl Default for JumpTokenBundle {
         Self {
             sprite_sheet_bundle: LdtkSpriteSheetBundle::default(),
             token_marker: JumpTokenMarker,
-            token_status: JumpTokenStatus {
-                timer: Timer::from_seconds(5., TimerMode::Once),
-                active: true,
-            },
+            trigger: TriggerArea::new(Vec2::splat(7.))
+                .on_enter(TriggerEffect::pickup(SoundEffectType::SmallPowerup))
+                .respawn_after(Duration::from_secs(5)),
             animation_timer: AnimationTimer(Timer::new(
                 Duration::from_millis(300),
                 TimerMode::Repeating,
@@ -48,11 +40,14 @@ impl Default for JumpTokenBundle {
     }
 }
 
-pub fn attach_timer(mut commands: Commands, query: Query<Entity, Added<JumpTokenMarker>>) {
-    let mut rng = rand::thread_rng();
+pub fn attach_timer(
+    mut commands: Commands,
+    query: Query<Entity, Added<JumpTokenMarker>>,
+    mut game_rng: ResMut<GameRng>,
+) {
     for entity in query.iter() {
         commands.entity(entity).insert(AnimationTimer(Timer::new(
-            Duration::from_millis(rng.gen_range(200..400)),
+            Duration::from_millis(game_rng.0.gen_range(200..400)),
             TimerMode::Repeating,
         )));
     }
@@ -70,57 +65,29 @@ pub fn animate_jump_token(
     }
 }
 
-pub fn add_jump_token_sensor(
+pub fn collect_jump_token(
     mut commands: Commands,
-    query_jump_token: Query<Entity, Added<JumpTokenMarker>>,
+    mut trigger_events: EventReader<TriggerEvent>,
+    query_tokens: Query<(), With<JumpTokenMarker>>,
+    mut query_player: Query<(Entity, Option<&mut AirJumpTokens>), With<PlayerMarker>>,
 ) {
-    for token in query_jump_token.iter() {
-        commands.entity(token).with_children(|parent| {
-            parent.spawn((
-                Collider::cuboid(7., 7.),
-                Sensor,
-                ActiveEvents::COLLISION_EVENTS,
-                JumpTokenSensorMarker,
-                TransformBundle::from_transform(Transform::from_xyz(0., 0., 0.)),
-            ));
-        });
-    }
-}
-
-pub fn check_jump_token_acquire(
-    rapier_context: Res<RapierContext>,
-    mut query_token_sensor: Query<(&mut Parent, Entity), With<JumpTokenSensorMarker>>,
-    mut query_player: Query<&mut PlayerInventory, With<PlayerMarker>>,
-    query_player_collider: Query<Entity, With<PlayerColliderMarker>>,
-    mut query_token: Query<(&mut Visibility, &mut JumpTokenStatus)>,
-    time: Res<Time>,
-    mut sound_effect_event_writer: EventWriter<SoundEffectEvent>,
-) {
-    let Ok(mut inventory) = query_player.get_single_mut() else {
-        return;
-    };
-    let Ok(player_collider) = query_player_collider.get_single() else {
+    let Ok((player, mut air_jump_tokens)) = query_player.get_single_mut() else {
         return;
     };
 
-    for (token, token_sensor_entity) in query_token_sensor.iter_mut() {
-        let (mut token_visibility, mut token_status) = query_token.get_mut(token.get()).unwrap();
-        if rapier_context.intersection_pair(player_collider, token_sensor_entity) == Some(true)
-            && token_status.active
-        {
-            inventory.air_jumps += 1;
-            // println!("ADDED JUMP");
-            sound_effect_event_writer.send(SoundEffectEvent(SoundEffectType::SmallPowerup));
-            token_status.active = false;
-            *token_visibility = Visibility::Hidden;
-        } else if !token_status.active {
-            token_status.timer.tick(time.delta());
-            if token_status.timer.just_finished() {
-                // println!("TOKEN RESETTING");
-                token_status.timer.reset();
-                token_status.active = true;
-                *token_visibility = Visibility::Inherited;
-            }
+    let mut collected = 0;
+    for event in trigger_events.read() {
+        if event.kind == TriggerKind::Enter && query_tokens.contains(event.trigger) {
+            collected += 1;
+        }
+    }
+    if collected == 0 {
+        return;
+    }
+    match air_jump_tokens.as_mut() {
+        Some(air_jump_tokens) => air_jump_tokens.0 += collected,
+        None => {
+            commands.entity(player).insert(AirJumpTokens(collected));
         }
     }
 }
@@ -1,20 +1,26 @@
-use std::time::Duration;
+use std::{ops::RangeInclusive, time::Duration};
 
 use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
 use bevy_rapier2d::prelude::*;
 
 use crate::{
+    echo::Echo,
     entities::platform::add_platform_colliders,
+    input::GameplayInput,
     player::{animation::AnimationTimer, PlayerColliderMarker, SetCheckpointEvent},
     sound_effects::{SoundEffectEvent, SoundEffectType},
 };
 
 use super::{
+    fields::{EntityFieldErrors, EntityFields},
     platform::{PlatformInfo, PlatformMarker},
     INTERACT_KEYCODE,
 };
 
+/// One row of `spritesheets/lever.png` per lever id
+pub const LEVER_IDS: RangeInclusive<i32> = 1..=4;
+
 #[derive(Component, Debug)]
 pub struct LeverMarker;
 
@@ -40,7 +46,7 @@ pub struct LeverBundle {
     sprite_sheet_bundle: LdtkSpriteSheetBundle,
     lever_marker: LeverMarker,
     #[with(lever_initial_state)]
-    lever_state: LeverState,
+    lever_state: (LeverState, EntityFieldErrors),
     animation_timer: AnimationTimer,
     animation_state: LeverAnimationState,
 }
@@ -50,10 +56,13 @@ impl Default for LeverBundle {
         Self {
             sprite_sheet_bundle: LdtkSpriteSheetBundle::default(),
             lever_marker: LeverMarker,
-            lever_state: LeverState {
-                id: 0,
-                activated: false,
-            },
+            lever_state: (
+                LeverState {
+                    id: 0,
+                    activated: false,
+                },
+                EntityFieldErrors::default(),
+            ),
             animation_timer: AnimationTimer(Timer::new(
                 Duration::from_millis(100),
                 TimerMode::Repeating,
@@ -63,11 +72,14 @@ impl Default for LeverBundle {
     }
 }
 
-fn lever_initial_state(ei: &EntityInstance) -> LeverState {
-    LeverState {
-        id: *ei.get_int_field("lever_id").unwrap() as usize,
+fn lever_initial_state(ei: &EntityInstance) -> (LeverState, EntityFieldErrors) {
+    let mut fields = EntityFields::new(ei);
+    // levers with a broken id don't move any platform
+    let lever_state = LeverState {
+        id: fields.int_in_range("lever_id", LEVER_IDS, 0).max(0) as usize,
         activated: false,
-    }
+    };
+    (lever_state, fields.finish())
 }
 
 pub fn add_lever_interaction(
@@ -75,7 +87,8 @@ pub fn add_lever_interaction(
     mut query_levers: Query<(&mut TextureAtlas, &LeverState, Entity), Added<LeverMarker>>,
 ) {
     for (mut atlas, state, lever) in query_levers.iter_mut() {
-        let base_index = (state.id - 1) * 5;
+        let row = (state.id as i32).clamp(*LEVER_IDS.start(), *LEVER_IDS.end()) - 1;
+        let base_index = row as usize * 5;
         atlas.index = base_index;
         commands.entity(lever).with_children(|parent| {
             parent.spawn((
@@ -132,25 +145,36 @@ pub fn check_lever_interacting(
     rapier_context: Res<RapierContext>,
     mut query_lever_sensor: Query<(&mut Parent, Entity), With<LeverSensorMarker>>,
     query_player_collider: Query<Entity, With<PlayerColliderMarker>>,
-    mut query_lever: Query<(&mut LeverState, &mut LeverAnimationState)>,
+    query_echoes: Query<(&Echo, &Transform)>,
+    mut query_lever: Query<(&mut LeverState, &mut LeverAnimationState, &Transform)>,
     mut query_platforms: Query<
         (&mut PlatformInfo, &mut TextureAtlas, Entity),
         With<PlatformMarker>,
     >,
-    keys: Res<ButtonInput<KeyCode>>,
+    keys: Res<GameplayInput>,
     mut checkpoint_event_writer: EventWriter<SetCheckpointEvent>,
     mut sound_effect_event_writer: EventWriter<SoundEffectEvent>,
 ) {
     let Ok(player_collider) = query_player_collider.get_single() else {
         return;
     };
-    if !keys.just_pressed(INTERACT_KEYCODE) {
-        return;
-    }
 
     for (lever, lever_sensor) in query_lever_sensor.iter_mut() {
-        let (mut lever_state, mut animation_state) = query_lever.get_mut(lever.get()).unwrap();
-        if rapier_context.intersection_pair(player_collider, lever_sensor) != Some(true) {
+        let Ok((mut lever_state, mut animation_state, lever_transform)) =
+            query_lever.get_mut(lever.get())
+        else {
+            continue;
+        };
+        let pulled_by_player = keys.just_pressed(INTERACT_KEYCODE)
+            && rapier_context.intersection_pair(player_collider, lever_sensor) == Some(true);
+        // echoes are sensors too, which rapier doesn't report intersections between
+        let pulled_by_echo = query_echoes.iter().any(|(echo, echo_transform)| {
+            echo.pulls_lever(
+                echo_transform.translation.xy(),
+                lever_transform.translation.xy(),
+            )
+        });
+        if !pulled_by_player && !pulled_by_echo {
             continue;
         }
 
@@ -176,7 +200,10 @@ pub fn check_lever_interacting(
                 }
             }
         }
-        checkpoint_event_writer.send(SetCheckpointEvent);
+        // an echo can pull a lever wherever the player is, which may be no place to respawn
+        if pulled_by_player {
+            checkpoint_event_writer.send(SetCheckpointEvent);
+        }
     }
 
     // for lever in query_levers.iter() {
@@ -1,33 +1,39 @@
+use crate::camera::hud::OpenTextBoxEvent;
+use crate::sound_effects::SoundEffectEvent;
 use crate::state::LevelLoadingState;
 use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
+use bevy_rapier2d::prelude::PhysicsSet;
 
 pub mod clock;
+pub mod dash;
 pub mod door;
 pub mod double_jump;
+pub mod fields;
 pub mod goal;
 pub mod jump_token;
 pub mod key;
 pub mod lever;
 pub mod platform;
 pub mod sign;
+pub mod trigger;
 pub mod wall_jump;
 
 use clock::{animate_clock, ClockBundle};
+use dash::{animate_dash, collect_dash, DashBundle};
 use door::{add_door_interaction, animate_door, check_door_interacting, DoorBundle};
-use double_jump::{
-    add_double_jump_sensor, animate_double_jump, check_double_jump_acquire, DoubleJumpBundle,
-};
-use goal::{add_goal_sensor, check_goal_reached, GoalBundle};
-use jump_token::{
-    add_jump_token_sensor, animate_jump_token, attach_timer, check_jump_token_acquire,
-    JumpTokenBundle,
-};
-use key::{add_key_sensor, animate_key, check_key_interacting, KeyBundle};
+use double_jump::{animate_double_jump, collect_double_jump, DoubleJumpBundle};
+use fields::{report_entity_field_errors, LevelFieldErrors};
+use goal::{check_goal_reached, GoalBundle};
+use jump_token::{animate_jump_token, attach_timer, collect_jump_token, JumpTokenBundle};
+use key::{animate_key, collect_key, KeyBundle};
 use lever::{add_lever_interaction, animate_lever, check_lever_interacting, LeverBundle};
 use platform::{insert_platform_colliders, PlatformBundle};
-use sign::{add_sign_interaction, check_sign_interacting, SignBundle};
-use wall_jump::{add_wall_jump_sensor, animate_wall_jump, check_wall_jump_acquire, WallJumpBundle};
+use sign::{check_sign_reading, SignBundle};
+use trigger::{
+    add_trigger_sensors, check_triggers, consume_triggers, TriggerEffectSet, TriggerEvent,
+};
+use wall_jump::{animate_wall_jump, collect_wall_jump, WallJumpBundle};
 
 pub struct EntityManagementPlugin;
 
@@ -35,7 +41,11 @@ pub const INTERACT_KEYCODE: KeyCode = KeyCode::KeyX;
 
 impl Plugin for EntityManagementPlugin {
     fn build(&self, app: &mut App) {
-        app.register_ldtk_entity::<DoorBundle>("LockedDoor")
+        app.init_resource::<LevelFieldErrors>()
+            .add_event::<SoundEffectEvent>()
+            .add_event::<OpenTextBoxEvent>()
+            .add_event::<TriggerEvent>()
+            .register_ldtk_entity::<DoorBundle>("LockedDoor")
             .register_ldtk_entity::<KeyBundle>("Key")
             .register_ldtk_entity::<ClockBundle>("Clock")
             .register_ldtk_entity::<JumpTokenBundle>("JumpToken")
@@ -43,39 +53,54 @@ impl Plugin for EntityManagementPlugin {
             .register_ldtk_entity::<LeverBundle>("Lever")
             .register_ldtk_entity::<PlatformBundle>("LeverPlatform")
             .register_ldtk_entity::<GoalBundle>("Goal")
+            // older level packs call the goal "Exit"
+            .register_ldtk_entity::<GoalBundle>("Exit")
             .register_ldtk_entity::<WallJumpBundle>("WallJump")
+            .register_ldtk_entity::<DashBundle>("Dash")
             .register_ldtk_entity::<SignBundle>("Sign")
             .add_systems(
                 Update,
                 (
                     add_door_interaction,
-                    check_door_interacting,
-                    add_key_sensor,
-                    check_key_interacting,
+                    add_trigger_sensors,
                     animate_clock,
-                    add_jump_token_sensor,
-                    check_jump_token_acquire,
-                    add_double_jump_sensor,
-                    check_double_jump_acquire,
                     add_lever_interaction,
-                    check_lever_interacting,
                     insert_platform_colliders,
                     (
-                        add_goal_sensor,
-                        check_goal_reached,
                         animate_lever,
                         animate_door,
                         animate_jump_token,
                         animate_wall_jump,
                         animate_double_jump,
+                        animate_dash,
                         attach_timer,
-                        add_wall_jump_sensor,
-                        check_wall_jump_acquire,
-                        add_sign_interaction,
-                        check_sign_interacting,
                         animate_key,
+                        report_entity_field_errors,
+                    ),
+                )
+                    .run_if(in_state(LevelLoadingState::Loaded)),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    check_door_interacting,
+                    check_lever_interacting,
+                    (
+                        check_triggers.before(TriggerEffectSet),
+                        (
+                            collect_key,
+                            collect_jump_token,
+                            collect_double_jump,
+                            collect_wall_jump,
+                            collect_dash,
+                            check_goal_reached,
+                            check_sign_reading,
+                        )
+                            .in_set(TriggerEffectSet),
+                        consume_triggers.after(TriggerEffectSet),
                     ),
                 )
+                    .before(PhysicsSet::SyncBackend)
                     .run_if(in_state(LevelLoadingState::Loaded)),
             );
     }
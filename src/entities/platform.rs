@@ -1,7 +1,14 @@
+use std::ops::RangeInclusive;
+
 use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
 use bevy_rapier2d::prelude::*;
 
+use super::fields::{EntityFieldErrors, EntityFields};
+
+/// One row of `spritesheets/leverplatform.png` per platform id
+pub const PLATFORM_IDS: RangeInclusive<i32> = 1..=4;
+
 #[derive(Component, Debug)]
 pub struct PlatformMarker;
 
@@ -20,7 +27,7 @@ pub struct PlatformBundle {
     sprite_sheet_bundle: LdtkSpriteSheetBundle,
     platform_marker: PlatformMarker,
     #[with(door_initial_status)]
-    platform_state: PlatformInfo,
+    platform_state: (PlatformInfo, EntityFieldErrors),
 }
 
 impl Default for PlatformBundle {
@@ -28,19 +35,25 @@ impl Default for PlatformBundle {
         Self {
             sprite_sheet_bundle: LdtkSpriteSheetBundle::default(),
             platform_marker: PlatformMarker,
-            platform_state: PlatformInfo {
-                id: 0,
-                active: true,
-            },
+            platform_state: (
+                PlatformInfo {
+                    id: 0,
+                    active: true,
+                },
+                EntityFieldErrors::default(),
+            ),
         }
     }
 }
 
-fn door_initial_status(ei: &EntityInstance) -> PlatformInfo {
-    PlatformInfo {
-        id: *ei.get_int_field("platform_id").unwrap() as usize,
-        active: *ei.get_bool_field("init_state").unwrap(),
-    }
+fn door_initial_status(ei: &EntityInstance) -> (PlatformInfo, EntityFieldErrors) {
+    let mut fields = EntityFields::new(ei);
+    // platforms with a broken id aren't moved by any lever
+    let platform_info = PlatformInfo {
+        id: fields.int_in_range("platform_id", PLATFORM_IDS, 0).max(0) as usize,
+        active: fields.bool("init_state", true),
+    };
+    (platform_info, fields.finish())
 }
 
 pub fn insert_platform_colliders(
@@ -48,7 +61,8 @@ pub fn insert_platform_colliders(
     mut query_doors: Query<(&PlatformInfo, &mut TextureAtlas, Entity), Added<PlatformMarker>>,
 ) {
     for (platform_info, mut atlas, platform) in query_doors.iter_mut() {
-        let base_index = (platform_info.id - 1) * 2;
+        let row = (platform_info.id as i32).clamp(*PLATFORM_IDS.start(), *PLATFORM_IDS.end()) - 1;
+        let base_index = row as usize * 2;
         if platform_info.active {
             add_platform_colliders(&mut commands, platform);
             atlas.index = base_index;
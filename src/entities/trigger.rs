@@ -0,0 +1,255 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    input::GameplayInput,
+    player::{PlayerColliderMarker, SetCheckpointEvent},
+    sound_effects::{SoundEffectEvent, SoundEffectType},
+};
+
+use super::INTERACT_KEYCODE;
+
+/// An area that sends [`TriggerEvent`]s when the player touches it. The sensor is added as a child
+/// when the entity spawns, and what's common to every pickup, like sounds, checkpoints and
+/// respawning, is handled by the [`TriggerEffect`]s. Anything else is up to systems in
+/// [`TriggerEffectSet`] reading the events.
+#[derive(Component, Debug)]
+pub struct TriggerArea {
+    /// Half the width and height of the sensor
+    pub half_size: Vec2,
+    pub on_enter: Option<TriggerEffect>,
+    pub on_exit: Option<TriggerEffect>,
+    /// When the player presses the interact key while touching it
+    pub on_interact: Option<TriggerEffect>,
+    /// How long a consumed trigger is hidden before it can be triggered again. Consumed triggers
+    /// without one are despawned.
+    pub respawn_after: Option<Duration>,
+    active: bool,
+    touching: bool,
+    respawn_timer: Timer,
+}
+
+impl TriggerArea {
+    pub fn new(half_size: Vec2) -> Self {
+        Self {
+            half_size,
+            on_enter: None,
+            on_exit: None,
+            on_interact: None,
+            respawn_after: None,
+            active: true,
+            touching: false,
+            respawn_timer: Timer::default(),
+        }
+    }
+
+    pub fn on_enter(self, effect: TriggerEffect) -> Self {
+        Self {
+            on_enter: Some(effect),
+            ..self
+        }
+    }
+
+    pub fn on_exit(self, effect: TriggerEffect) -> Self {
+        Self {
+            on_exit: Some(effect),
+            ..self
+        }
+    }
+
+    pub fn on_interact(self, effect: TriggerEffect) -> Self {
+        Self {
+            on_interact: Some(effect),
+            ..self
+        }
+    }
+
+    pub fn respawn_after(self, duration: Duration) -> Self {
+        Self {
+            respawn_after: Some(duration),
+            ..self
+        }
+    }
+
+    /// Whether it can be triggered, which consumed triggers can't until they respawn
+    pub fn active(&self) -> bool {
+        self.active
+    }
+
+    /// Makes a consumed trigger available again right away
+    pub fn respawn(&mut self) {
+        self.active = true;
+        self.respawn_timer.reset();
+    }
+
+    fn effect(&self, kind: TriggerKind) -> Option<&TriggerEffect> {
+        match kind {
+            TriggerKind::Enter => self.on_enter.as_ref(),
+            TriggerKind::Exit => self.on_exit.as_ref(),
+            TriggerKind::Interact => self.on_interact.as_ref(),
+        }
+    }
+}
+
+/// What a [`TriggerArea`] does by itself when it's triggered, besides sending a [`TriggerEvent`]
+#[derive(Debug, Default, Clone)]
+pub struct TriggerEffect {
+    pub sound: Option<SoundEffectType>,
+    /// Moves the player's checkpoint to where they are
+    pub set_checkpoint: bool,
+    /// Despawns the trigger, or hides it until it respawns
+    pub consume: bool,
+}
+
+impl TriggerEffect {
+    /// A pickup that's used up and plays `sound`
+    pub fn pickup(sound: SoundEffectType) -> Self {
+        Self {
+            sound: Some(sound),
+            set_checkpoint: false,
+            consume: true,
+        }
+    }
+
+    pub fn set_checkpoint(self) -> Self {
+        Self {
+            set_checkpoint: true,
+            ..self
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerKind {
+    Enter,
+    Exit,
+    Interact,
+}
+
+/// Sent when the player triggers one of the effects of a [`TriggerArea`]
+#[derive(Event, Debug, Clone, Copy)]
+pub struct TriggerEvent {
+    /// The entity with the [`TriggerArea`]
+    pub trigger: Entity,
+    pub kind: TriggerKind,
+}
+
+/// Systems in `FixedUpdate` reacting to [`TriggerEvent`]s, which run after the events are sent and
+/// before consumed triggers are despawned
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TriggerEffectSet;
+
+#[derive(Component, Debug)]
+pub struct TriggerSensorMarker;
+
+pub fn add_trigger_sensors(
+    mut commands: Commands,
+    query_triggers: Query<(Entity, &TriggerArea), Added<TriggerArea>>,
+) {
+    for (entity, trigger) in query_triggers.iter() {
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn((
+                Collider::cuboid(trigger.half_size.x, trigger.half_size.y),
+                Sensor,
+                ActiveEvents::COLLISION_EVENTS,
+                TransformBundle::from_transform(Transform::from_xyz(0., 0., 0.)),
+                TriggerSensorMarker,
+            ));
+        });
+    }
+}
+
+/// Sends a [`TriggerEvent`] for every effect the player triggered this tick, and plays its sound
+/// and sets the checkpoint
+pub fn check_triggers(
+    rapier_context: Res<RapierContext>,
+    query_sensors: Query<(&Parent, Entity), With<TriggerSensorMarker>>,
+    mut query_triggers: Query<&mut TriggerArea>,
+    query_player_collider: Query<Entity, With<PlayerColliderMarker>>,
+    keys: Res<GameplayInput>,
+    mut trigger_event_writer: EventWriter<TriggerEvent>,
+    mut checkpoint_event_writer: EventWriter<SetCheckpointEvent>,
+    mut sound_effect_event_writer: EventWriter<SoundEffectEvent>,
+) {
+    let Ok(player_collider) = query_player_collider.get_single() else {
+        return;
+    };
+
+    for (parent, sensor) in query_sensors.iter() {
+        let Ok(mut trigger) = query_triggers.get_mut(parent.get()) else {
+            continue;
+        };
+        // a trigger respawning under the player is entered again
+        let touching = trigger.active
+            && rapier_context.intersection_pair(player_collider, sensor) == Some(true);
+        let mut kinds = Vec::new();
+        if touching && !trigger.touching {
+            kinds.push(TriggerKind::Enter);
+        } else if !touching && trigger.touching {
+            kinds.push(TriggerKind::Exit);
+        }
+        if touching && keys.just_pressed(INTERACT_KEYCODE) {
+            kinds.push(TriggerKind::Interact);
+        }
+        trigger.touching = touching;
+
+        for kind in kinds {
+            let Some(effect) = trigger.effect(kind) else {
+                continue;
+            };
+            if let Some(sound) = effect.sound {
+                sound_effect_event_writer.send(SoundEffectEvent(sound));
+            }
+            if effect.set_checkpoint {
+                checkpoint_event_writer.send(SetCheckpointEvent);
+            }
+            trigger_event_writer.send(TriggerEvent {
+                trigger: parent.get(),
+                kind,
+            });
+        }
+    }
+}
+
+/// Respawns hidden triggers once their time is up, and despawns or hides the triggers consumed
+/// this tick
+pub fn consume_triggers(
+    mut commands: Commands,
+    mut trigger_events: EventReader<TriggerEvent>,
+    mut query_triggers: Query<(&mut TriggerArea, &mut Visibility)>,
+    time: Res<Time>,
+) {
+    for (mut trigger, mut visibility) in query_triggers.iter_mut() {
+        if trigger.active {
+            continue;
+        }
+        trigger.respawn_timer.tick(time.delta());
+        if trigger.respawn_timer.finished() {
+            trigger.respawn();
+            *visibility = Visibility::Inherited;
+        }
+    }
+
+    for event in trigger_events.read() {
+        let Ok((mut trigger, mut visibility)) = query_triggers.get_mut(event.trigger) else {
+            continue;
+        };
+        if !trigger.active
+            || !trigger
+                .effect(event.kind)
+                .is_some_and(|effect| effect.consume)
+        {
+            continue;
+        }
+        match trigger.respawn_after {
+            Some(duration) => {
+                trigger.active = false;
+                trigger.respawn_timer = Timer::new(duration, TimerMode::Once);
+                *visibility = Visibility::Hidden;
+            }
+            None => commands.entity(event.trigger).despawn_recursive(),
+        }
+    }
+}
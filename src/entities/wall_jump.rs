@@ -2,27 +2,23 @@ use std::time::Duration;
 
 use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
-use bevy_rapier2d::prelude::*;
 
 use crate::{
-    player::{
-        animation::AnimationTimer, PlayerColliderMarker, PlayerInventory, PlayerMarker,
-        SetCheckpointEvent,
-    },
-    sound_effects::{SoundEffectEvent, SoundEffectType},
+    player::{abilities::wall_jump::WallJump, animation::AnimationTimer, PlayerMarker},
+    sound_effects::SoundEffectType,
 };
 
-#[derive(Component, Debug)]
-pub struct WallJumpMarker;
+use super::trigger::{TriggerArea, TriggerEffect, TriggerEvent, TriggerKind};
 
 #[derive(Component, Debug)]
-pub struct WallJumpSensorMarker;
+pub struct WallJumpMarker;
 
 #[derive(Bundle, LdtkEntity)]
 pub struct WallJumpBundle {
     #[sprite_sheet_bundle("../assets/spritesheets/walljump.png", 16, 16, 4, 1, 0, 0, 0)]
     sprite_sheet_bundle: LdtkSpriteSheetBundle,
     wall_jump_marker: WallJumpMarker,
+    trigger: TriggerArea,
     animation_timer: AnimationTimer,
 }
 
@@ -31,6 +27,8 @@ impl Default for WallJumpBundle {
         Self {
             sprite_sheet_bundle: LdtkSpriteSheetBundle::default(),
             wall_jump_marker: WallJumpMarker,
+            trigger: TriggerArea::new(Vec2::splat(7.))
+                .on_enter(TriggerEffect::pickup(SoundEffectType::BigPowerup).set_checkpoint()),
             animation_timer: AnimationTimer(Timer::new(
                 Duration::from_millis(300),
                 TimerMode::Repeating,
@@ -51,48 +49,19 @@ pub fn animate_wall_jump(
     }
 }
 
-pub fn add_wall_jump_sensor(
-    mut commands: Commands,
-    query_jump_token: Query<Entity, Added<WallJumpMarker>>,
-) {
-    for token in query_jump_token.iter() {
-        commands.entity(token).with_children(|parent| {
-            parent.spawn((
-                Collider::cuboid(7., 7.),
-                Sensor,
-                ActiveEvents::COLLISION_EVENTS,
-                WallJumpSensorMarker,
-                TransformBundle::from_transform(Transform::from_xyz(0., 0., 0.)),
-            ));
-        });
-    }
-}
-
-pub fn check_wall_jump_acquire(
+pub fn collect_wall_jump(
     mut commands: Commands,
-    rapier_context: Res<RapierContext>,
-    mut q_wall_jump_sensor: Query<(&mut Parent, Entity), With<WallJumpSensorMarker>>,
-    mut query_player: Query<&mut PlayerInventory, With<PlayerMarker>>,
-    query_player_collider: Query<Entity, With<PlayerColliderMarker>>,
-    mut q_wall_jump: Query<Entity>,
-    mut checkpoint_event_writer: EventWriter<SetCheckpointEvent>,
-    mut sound_effect_event_writer: EventWriter<SoundEffectEvent>,
+    mut trigger_events: EventReader<TriggerEvent>,
+    query_pickups: Query<(), With<WallJumpMarker>>,
+    query_player: Query<Entity, (With<PlayerMarker>, Without<WallJump>)>,
 ) {
-    let Ok(mut inventory) = query_player.get_single_mut() else {
-        return;
-    };
-    let Ok(player_collider) = query_player_collider.get_single() else {
+    let Ok(player) = query_player.get_single() else {
         return;
     };
 
-    for (wall_jump, dj_sensor_entity) in q_wall_jump_sensor.iter_mut() {
-        let dj_entity = q_wall_jump.get_mut(wall_jump.get()).unwrap();
-        if rapier_context.intersection_pair(player_collider, dj_sensor_entity) == Some(true) {
-            sound_effect_event_writer.send(SoundEffectEvent(SoundEffectType::BigPowerup));
-            inventory.has_wall_jump = true;
-            // println!("PLAYER ENABLED WALL JUMPS");
-            commands.entity(dj_entity).despawn_recursive();
-            checkpoint_event_writer.send(SetCheckpointEvent);
+    for event in trigger_events.read() {
+        if event.kind == TriggerKind::Enter && query_pickups.contains(event.trigger) {
+            commands.entity(player).insert(WallJump::default());
         }
     }
 }
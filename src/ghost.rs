@@ -0,0 +1,309 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use bevy_rapier2d::prelude::PhysicsSet;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    camera::{CameraPanning, CameraPanningState, PlayerCameraMarker, PLAYER_RENDER_LAYER},
+    level::{manifest::LevelManifest, LevelTimer, LevelWrap, WrapMode},
+    physics::MAX_INTERPOLATION_DISTANCE,
+    player::{LevelCompletedEvent, PlayerMarker, PlayerStatus},
+    save::SaveDirectory,
+    state::{LevelLoadingState, TargetLevel, TargetPack},
+};
+
+/// Folder inside the save directory ghosts are written to
+const GHOST_DIRECTORY_NAME: &str = "ghosts";
+
+const GHOST_COLOR: Color = Color::srgba(1., 1., 1., 0.4);
+
+pub struct GhostManagementPlugin;
+
+impl Plugin for GhostManagementPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GhostRecorder>()
+            .init_resource::<PersonalBestGhosts>()
+            .add_systems(
+                Update,
+                (spawn_ghost, save_personal_best_ghost, show_ghost)
+                    .run_if(in_state(LevelLoadingState::Loaded)),
+            )
+            .add_systems(
+                FixedUpdate,
+                (record_ghost_frame, advance_ghost)
+                    .after(PhysicsSet::Writeback)
+                    .run_if(in_state(LevelLoadingState::Loaded))
+                    .run_if(player_racing),
+            );
+    }
+}
+
+/// Where the player was during one fixed tick
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct GhostFrame {
+    pub x: f32,
+    pub y: f32,
+    pub sprite_index: u8,
+    pub flip_x: bool,
+}
+
+/// The path of the player through a level, one frame per fixed tick from the moment the player
+/// gained control
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GhostPath {
+    /// Level time of the run the path was recorded in, in seconds
+    pub level_time: f32,
+    pub frames: Vec<GhostFrame>,
+}
+
+/// Path of the player through the current level so far
+#[derive(Resource, Default)]
+pub struct GhostRecorder(pub Vec<GhostFrame>);
+
+/// The fastest path through each level played since launch, keyed by pack id and level iid.
+/// `None` if there is no ghost for that level.
+#[derive(Resource, Default)]
+pub struct PersonalBestGhosts(pub HashMap<(String, String), Option<GhostPath>>);
+
+/// A translucent copy of the player following a recorded path
+#[derive(Component)]
+pub struct Ghost {
+    path: GhostPath,
+    /// Number of fixed ticks the player has been racing the ghost for
+    ticks: usize,
+    level_size: Vec2,
+    wrap_mode: WrapMode,
+}
+
+#[derive(Debug, Error)]
+pub enum GhostError {
+    #[error("could not access ghost file: {0}")]
+    Io(#[from] io::Error),
+    #[error("could not parse ghost file: {0}")]
+    Parse(#[from] ron::error::SpannedError),
+    #[error("could not serialize ghost file: {0}")]
+    Serialize(#[from] ron::Error),
+}
+
+fn ghost_path(directory: &Path, pack_id: &str, level_iid: &str) -> PathBuf {
+    directory
+        .join(GHOST_DIRECTORY_NAME)
+        .join(pack_id)
+        .join(format!("{level_iid}.ghost.ron"))
+}
+
+/// Reads the ghost of a level from `directory`, returning `None` if there is none yet
+pub fn read_ghost(
+    directory: &Path,
+    pack_id: &str,
+    level_iid: &str,
+) -> Result<Option<GhostPath>, GhostError> {
+    match fs::read_to_string(ghost_path(directory, pack_id, level_iid)) {
+        Ok(contents) => Ok(Some(ron::from_str(&contents)?)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn write_ghost(
+    directory: &Path,
+    pack_id: &str,
+    level_iid: &str,
+    ghost: &GhostPath,
+) -> Result<(), GhostError> {
+    let path = ghost_path(directory, pack_id, level_iid);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, ron::to_string(ghost)?)?;
+    Ok(())
+}
+
+/// Ghosts only move while the player is racing them, from the end of the intro camera pan until
+/// the goal is reached
+fn player_racing(
+    camera_panning: Res<CameraPanning>,
+    query_player: Query<&PlayerStatus, With<PlayerMarker>>,
+) -> bool {
+    camera_panning.panning_state == CameraPanningState::WaitingAtPlayer
+        && query_player
+            .get_single()
+            .is_ok_and(|player_status| !player_status.level_finished)
+}
+
+/// Spawns the ghost of the current level next to the player, in the same level entity so they
+/// share coordinates
+fn spawn_ghost(
+    mut commands: Commands,
+    query_player: Query<(&Parent, &Transform, &Handle<Image>, &TextureAtlas), Added<PlayerMarker>>,
+    query_level: Query<&LayerMetadata>,
+    mut ghost_recorder: ResMut<GhostRecorder>,
+    mut personal_best_ghosts: ResMut<PersonalBestGhosts>,
+    save_directory: Res<SaveDirectory>,
+    target_pack: Res<TargetPack>,
+    target_level: Res<TargetLevel>,
+    level_manifest: Res<LevelManifest>,
+    level_wrap: Res<LevelWrap>,
+) {
+    let Ok((parent, player_transform, texture, atlas)) = query_player.get_single() else {
+        return;
+    };
+    ghost_recorder.0.clear();
+    let Some((pack, level)) = level_manifest.level(target_pack.0, target_level.0) else {
+        return;
+    };
+    let ghost = personal_best_ghosts
+        .0
+        .entry((pack.id.clone(), level.iid.clone()))
+        .or_insert_with(|| {
+            let directory = save_directory.0.as_ref()?;
+            read_ghost(directory, &pack.id, &level.iid).unwrap_or_else(|e| {
+                warn!("could not load ghost: {e}");
+                None
+            })
+        });
+    let Some(ghost) = ghost.clone() else {
+        return;
+    };
+    let Some(level_size) = query_level
+        .iter()
+        .find(|layer| layer.layer_instance_type == bevy_ecs_ldtk::ldtk::Type::IntGrid)
+        .map(|layer| Vec2::new(layer.c_wid as f32 * 16., layer.c_hei as f32 * 16.))
+    else {
+        return;
+    };
+    commands.entity(parent.get()).with_children(|parent| {
+        parent
+            .spawn(SpriteBundle {
+                sprite: Sprite {
+                    color: GHOST_COLOR,
+                    ..default()
+                },
+                texture: texture.clone(),
+                // just behind the player
+                transform: Transform::from_translation(
+                    player_transform.translation - Vec3::Z * 0.1,
+                ),
+                ..default()
+            })
+            .insert(TextureAtlas {
+                layout: atlas.layout.clone(),
+                index: 0,
+            })
+            .insert(PLAYER_RENDER_LAYER)
+            .insert(Ghost {
+                path: ghost,
+                ticks: 0,
+                level_size,
+                wrap_mode: level_wrap.0,
+            });
+    });
+}
+
+fn record_ghost_frame(
+    query_player: Query<(&Transform, &Sprite, &TextureAtlas), With<PlayerMarker>>,
+    mut ghost_recorder: ResMut<GhostRecorder>,
+) {
+    let Ok((transform, sprite, atlas)) = query_player.get_single() else {
+        return;
+    };
+    ghost_recorder.0.push(GhostFrame {
+        x: transform.translation.x,
+        y: transform.translation.y,
+        sprite_index: atlas.index as u8,
+        flip_x: sprite.flip_x,
+    });
+}
+
+fn advance_ghost(mut query_ghost: Query<&mut Ghost>) {
+    for mut ghost in query_ghost.iter_mut() {
+        ghost.ticks += 1;
+    }
+}
+
+/// Places the ghost between its last two frames, on the side of the level seams the player
+/// camera is looking at
+fn show_ghost(
+    fixed_time: Res<Time<Fixed>>,
+    mut query_ghost: Query<(&Ghost, &mut Transform, &mut Sprite, &mut TextureAtlas)>,
+    query_player_camera: Query<&GlobalTransform, With<PlayerCameraMarker>>,
+) {
+    let Ok(camera_transform) = query_player_camera.get_single() else {
+        return;
+    };
+    for (ghost, mut transform, mut sprite, mut atlas) in query_ghost.iter_mut() {
+        // frame `i` was recorded at the end of tick `i + 1`, and the ghost stays at the goal once
+        // its path is over
+        let frame_index = ghost.ticks.min(ghost.path.frames.len()).saturating_sub(1);
+        let Some(frame) = ghost.path.frames.get(frame_index) else {
+            continue;
+        };
+        let previous = &ghost.path.frames[frame_index.saturating_sub(1)];
+        let previous_position = Vec2::new(previous.x, previous.y);
+        let position = Vec2::new(frame.x, frame.y);
+        let mut position = if previous_position.distance(position) > MAX_INTERPOLATION_DISTANCE {
+            position
+        } else {
+            previous_position.lerp(position, fixed_time.overstep_fraction())
+        };
+        // the player camera only covers one copy of the looping level, so show the ghost in the
+        // copy closest to it
+        let offset = camera_transform.translation().xy() - position;
+        if ghost.wrap_mode.horizontal() {
+            position.x += (offset.x / ghost.level_size.x).round() * ghost.level_size.x;
+        }
+        if ghost.wrap_mode.vertical() {
+            position.y += (offset.y / ghost.level_size.y).round() * ghost.level_size.y;
+        }
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+        sprite.flip_x = frame.flip_x;
+        atlas.index = frame.sprite_index as usize;
+    }
+}
+
+/// Keeps the path of the run that just finished if it beat the ghost of the level
+fn save_personal_best_ghost(
+    mut level_completed_events: EventReader<LevelCompletedEvent>,
+    ghost_recorder: Res<GhostRecorder>,
+    level_timer: Res<LevelTimer>,
+    mut personal_best_ghosts: ResMut<PersonalBestGhosts>,
+    save_directory: Res<SaveDirectory>,
+    target_pack: Res<TargetPack>,
+    target_level: Res<TargetLevel>,
+    level_manifest: Res<LevelManifest>,
+) {
+    for LevelCompletedEvent in level_completed_events.read() {
+        let Some((pack, level)) = level_manifest.level(target_pack.0, target_level.0) else {
+            continue;
+        };
+        let level_time = level_timer.0.elapsed_secs();
+        let ghost = personal_best_ghosts
+            .0
+            .entry((pack.id.clone(), level.iid.clone()))
+            .or_default();
+        if ghost
+            .as_ref()
+            .is_some_and(|ghost| ghost.level_time <= level_time)
+        {
+            continue;
+        }
+        let new_ghost = GhostPath {
+            level_time,
+            frames: ghost_recorder.0.clone(),
+        };
+        if let Some(directory) = &save_directory.0 {
+            if let Err(e) = write_ghost(directory, &pack.id, &level.iid, &new_ghost) {
+                warn!("could not write ghost: {e}");
+            }
+        }
+        *ghost = Some(new_ghost);
+    }
+}
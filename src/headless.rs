@@ -0,0 +1,21 @@
+use bevy::{prelude::*, render::render_resource::Shader};
+
+use crate::camera::{CameraPanning, CameraPanningState};
+
+/// Stands in for the rendering plugins when the game runs without a window
+pub struct HeadlessPlugin;
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        // what the rendering plugins would add, which the tilemap and LDtk plugins expect
+        app.init_asset::<Shader>()
+            .init_asset::<TextureAtlasLayout>()
+            .init_resource::<ClearColor>()
+            .add_systems(PreUpdate, skip_camera_pan);
+    }
+}
+
+/// There are no cameras to pan to the goal and back, so the player is in control right away
+fn skip_camera_pan(mut camera_panning: ResMut<CameraPanning>) {
+    camera_panning.panning_state = CameraPanningState::WaitingAtPlayer;
+}
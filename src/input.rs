@@ -0,0 +1,101 @@
+use bevy::{input::InputSystem, prelude::*};
+
+use crate::{entities::INTERACT_KEYCODE, player::abilities::dash::DASH_KEYCODE};
+
+/// Every key gameplay systems read, in the order their bits are stored in
+/// [`GameplayInput::keys`] and [`GameplayInput::presses`]
+pub const GAMEPLAY_KEYS: [KeyCode; 8] = [
+    KeyCode::ArrowLeft,
+    KeyCode::ArrowRight,
+    KeyCode::ArrowUp,
+    KeyCode::KeyZ,
+    INTERACT_KEYCODE,
+    KeyCode::KeyR,
+    KeyCode::Escape,
+    DASH_KEYCODE,
+];
+
+pub struct InputManagementPlugin;
+
+impl Plugin for InputManagementPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameplayInput>()
+            .init_resource::<PendingPresses>()
+            .add_systems(PreUpdate, buffer_presses.after(InputSystem))
+            .add_systems(
+                FixedPreUpdate,
+                update_gameplay_input.in_set(GameplayInputSet),
+            );
+    }
+}
+
+/// Systems in `FixedPreUpdate` that decide the [`GameplayInput`] of the coming tick. Anything
+/// replacing the input, like replays, runs after this set.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GameplayInputSet;
+
+/// The state of [`GAMEPLAY_KEYS`] for the current fixed tick. Systems in `FixedUpdate` read this
+/// instead of `ButtonInput<KeyCode>`, which only tracks presses per frame, so a press is seen by
+/// exactly one tick however many ticks run in a frame.
+#[derive(Resource, Default, Deref)]
+pub struct GameplayInput(ButtonInput<KeyCode>);
+
+impl GameplayInput {
+    /// Sets the input of a new tick. Bit `i` of `keys` is set if `GAMEPLAY_KEYS[i]` is held, and
+    /// bit `i` of `presses` if it was pressed since the last tick.
+    pub fn set(&mut self, keys: u8, presses: u8) {
+        self.0.clear();
+        for (i, key) in GAMEPLAY_KEYS.iter().enumerate() {
+            if presses & (1 << i) != 0 {
+                // release first so a key tapped again before the last tick saw it let go still
+                // counts as a new press
+                self.0.release(*key);
+                self.0.press(*key);
+                self.0.clear_just_released(*key);
+            } else if keys & (1 << i) != 0 {
+                self.0.press(*key);
+            } else {
+                self.0.release(*key);
+            }
+        }
+    }
+
+    /// The held keys of the current tick, as passed to [`GameplayInput::set`]
+    pub fn keys(&self) -> u8 {
+        key_bits(|key| self.0.pressed(key))
+    }
+
+    /// The keys pressed since the previous tick, as passed to [`GameplayInput::set`]
+    pub fn presses(&self) -> u8 {
+        key_bits(|key| self.0.just_pressed(key))
+    }
+}
+
+fn key_bits(mut is_set: impl FnMut(KeyCode) -> bool) -> u8 {
+    let mut bits = 0;
+    for (i, key) in GAMEPLAY_KEYS.iter().enumerate() {
+        if is_set(*key) {
+            bits |= 1 << i;
+        }
+    }
+    bits
+}
+
+/// Keys pressed since the last fixed tick
+#[derive(Resource, Default)]
+struct PendingPresses(u8);
+
+fn buffer_presses(keys: Res<ButtonInput<KeyCode>>, mut pending_presses: ResMut<PendingPresses>) {
+    pending_presses.0 |= key_bits(|key| keys.just_pressed(key));
+}
+
+fn update_gameplay_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut pending_presses: ResMut<PendingPresses>,
+    mut gameplay_input: ResMut<GameplayInput>,
+) {
+    // a key tapped within a single frame is still held for the tick that sees the press
+    let presses = pending_presses.0;
+    gameplay_input.set(key_bits(|key| keys.pressed(key)) | presses, presses);
+    pending_presses.0 = 0;
+}
@@ -0,0 +1,303 @@
+use std::str::FromStr;
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use bevy_rapier2d::prelude::*;
+use thiserror::Error;
+
+use super::LevelStartCounts;
+use crate::menus::CycleCount;
+
+/// LDtk entity field restricting the entity to some cycles of the level
+const ENTITY_CYCLES_FIELD: &str = "cycles";
+
+/// LDtk level field restricting layers to some cycles of the level, one `<layer>: <cycles>` entry
+/// per layer
+const LAYER_CYCLES_FIELD: &str = "layer_cycles";
+
+/// The cycles of a level something exists in, counting the first pass through the level as
+/// cycle 1. Parsed from a comma separated list of terms, any of which can match:
+/// - `odd` or `even`
+/// - `3`, only cycle 3
+/// - `2+`, cycle 2 and later
+/// - `<3`, cycles before cycle 3
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct CycleFilter(Vec<CycleTerm>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CycleTerm {
+    Odd,
+    Even,
+    Exactly(usize),
+    AtLeast(usize),
+    Below(usize),
+}
+
+#[derive(Debug, Error)]
+pub enum CycleFilterError {
+    #[error("invalid cycle term \"{0}\", expected odd, even, <n, n or n+")]
+    InvalidTerm(String),
+}
+
+impl FromStr for CycleFilter {
+    type Err = CycleFilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(|term| {
+                let term = term.trim();
+                let number = |n: &str| {
+                    n.trim()
+                        .parse()
+                        .map_err(|_| CycleFilterError::InvalidTerm(term.to_string()))
+                };
+                match term.to_lowercase().as_str() {
+                    "odd" => Ok(CycleTerm::Odd),
+                    "even" => Ok(CycleTerm::Even),
+                    _ => {
+                        if let Some(n) = term.strip_suffix('+') {
+                            number(n).map(CycleTerm::AtLeast)
+                        } else if let Some(n) = term.strip_prefix('<') {
+                            number(n).map(CycleTerm::Below)
+                        } else {
+                            number(term).map(CycleTerm::Exactly)
+                        }
+                    }
+                }
+            })
+            .collect::<Result<_, _>>()
+            .map(CycleFilter)
+    }
+}
+
+impl CycleFilter {
+    pub fn contains(&self, cycle: usize) -> bool {
+        self.0.iter().any(|term| match *term {
+            CycleTerm::Odd => !cycle.is_multiple_of(2),
+            CycleTerm::Even => cycle.is_multiple_of(2),
+            CycleTerm::Exactly(n) => cycle == n,
+            CycleTerm::AtLeast(n) => cycle >= n,
+            CycleTerm::Below(n) => cycle < n,
+        })
+    }
+}
+
+/// Whether the entity a [`CycleFilter`] is on was last shown
+#[derive(Component)]
+pub struct CycleActive(bool);
+
+/// The cycle of the current level the player is in, starting at 1
+pub(super) fn level_cycle(
+    cycle_count: &CycleCount,
+    level_start_counts: &LevelStartCounts,
+) -> usize {
+    cycle_count.0.saturating_sub(level_start_counts.cycles) + 1
+}
+
+fn parse_cycle_filter(cycles: &str, owner: &str) -> Option<CycleFilter> {
+    cycles
+        .parse()
+        .map_err(|e| warn!("{owner} will exist in every cycle: {e}"))
+        .ok()
+}
+
+/// Shows or hides `entity` and enables or disables the colliders on it and its descendants
+fn set_cycle_active(
+    commands: &mut Commands,
+    entity: Entity,
+    active: bool,
+    query_children: &Query<&Children>,
+    query_colliders: &Query<Has<ColliderDisabled>, With<Collider>>,
+) {
+    commands.entity(entity).insert(if active {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    });
+    for descendant in std::iter::once(entity).chain(query_children.iter_descendants(entity)) {
+        let Ok(disabled) = query_colliders.get(descendant) else {
+            continue;
+        };
+        if active && disabled {
+            commands.entity(descendant).remove::<ColliderDisabled>();
+        } else if !active && !disabled {
+            commands.entity(descendant).insert(ColliderDisabled);
+        }
+    }
+}
+
+/// Gives entities and layers of newly spawned levels the [`CycleFilter`] set on them in LDtk,
+/// hiding them right away if they're outside the current cycle
+pub(super) fn attach_cycle_filters(
+    mut commands: Commands,
+    query_entities: Query<(Entity, &EntityIid, &Parent), Added<EntityIid>>,
+    query_layers: Query<(Entity, &LayerMetadata, &Parent), Added<LayerMetadata>>,
+    query_parents: Query<&Parent>,
+    query_levels: Query<&LevelIid>,
+    query_children: Query<&Children>,
+    query_colliders: Query<Has<ColliderDisabled>, With<Collider>>,
+    query_ldtk_world: Query<&Handle<LdtkProject>>,
+    ldtk_projects: Res<Assets<LdtkProject>>,
+    cycle_count: Res<CycleCount>,
+    level_start_counts: Res<LevelStartCounts>,
+) {
+    if query_entities.is_empty() && query_layers.is_empty() {
+        return;
+    }
+    let Some(ldtk_project) = query_ldtk_world
+        .get_single()
+        .ok()
+        .and_then(|handle| ldtk_projects.get(handle))
+    else {
+        return;
+    };
+    let cycle = level_cycle(&cycle_count, &level_start_counts);
+    let mut attach = |entity: Entity, filter: CycleFilter| {
+        let active = filter.contains(cycle);
+        commands
+            .entity(entity)
+            .insert((filter, CycleActive(active)));
+        if !active {
+            set_cycle_active(
+                &mut commands,
+                entity,
+                false,
+                &query_children,
+                &query_colliders,
+            );
+        }
+    };
+    let raw_level = |layer: Entity| {
+        query_parents
+            .get(layer)
+            .and_then(|level| query_levels.get(level.get()))
+            .ok()
+            .and_then(|level_iid| ldtk_project.get_raw_level_by_iid(level_iid.get()))
+    };
+
+    for (entity, entity_iid, layer) in query_entities.iter() {
+        let Some(entity_instance) = raw_level(layer.get()).and_then(|level| {
+            level
+                .layer_instances
+                .iter()
+                .flatten()
+                .flat_map(|layer| &layer.entity_instances)
+                .find(|entity_instance| entity_instance.iid == entity_iid.as_str())
+        }) else {
+            continue;
+        };
+        let Ok(Some(cycles)) = entity_instance.get_maybe_string_field(ENTITY_CYCLES_FIELD) else {
+            continue;
+        };
+        if let Some(filter) = parse_cycle_filter(cycles, &entity_instance.identifier) {
+            attach(entity, filter);
+        }
+    }
+
+    for (layer, layer_metadata, level) in query_layers.iter() {
+        let Some(level) = query_levels
+            .get(level.get())
+            .ok()
+            .and_then(|level_iid| ldtk_project.get_raw_level_by_iid(level_iid.get()))
+        else {
+            continue;
+        };
+        let Ok(layer_cycles) = level.get_maybe_strings_field(LAYER_CYCLES_FIELD) else {
+            continue;
+        };
+        for entry in layer_cycles.iter().flatten() {
+            let Some((identifier, cycles)) = entry.split_once(':') else {
+                warn!("{LAYER_CYCLES_FIELD} entry \"{entry}\" should look like <layer>: <cycles>");
+                continue;
+            };
+            if identifier.trim() != layer_metadata.identifier {
+                continue;
+            }
+            if let Some(filter) = parse_cycle_filter(cycles, &layer_metadata.identifier) {
+                attach(layer, filter);
+            }
+        }
+    }
+}
+
+/// Hides entities and layers that are outside of the new cycle and disables their colliders, and
+/// brings back the ones inside it. Only the filters whose [`CycleActive`] flips are touched, since
+/// entities like jump tokens hide themselves.
+pub(super) fn apply_cycle_filters(
+    mut commands: Commands,
+    mut query_filtered: Query<(Entity, &CycleFilter, &mut CycleActive)>,
+    query_children: Query<&Children>,
+    query_colliders: Query<Has<ColliderDisabled>, With<Collider>>,
+    cycle_count: Res<CycleCount>,
+    level_start_counts: Res<LevelStartCounts>,
+) {
+    let cycle = level_cycle(&cycle_count, &level_start_counts);
+    for (entity, filter, mut cycle_active) in query_filtered.iter_mut() {
+        let active = filter.contains(cycle);
+        if cycle_active.0 != active {
+            cycle_active.0 = active;
+            set_cycle_active(
+                &mut commands,
+                entity,
+                active,
+                &query_children,
+                &query_colliders,
+            );
+        }
+    }
+}
+
+/// Disables colliders added to entities and layers outside of the current cycle, like the ones
+/// levers and doors add as they go
+pub(super) fn disable_added_colliders(
+    mut commands: Commands,
+    query_added: Query<Entity, (Added<Collider>, Without<ColliderDisabled>)>,
+    query_parents: Query<&Parent>,
+    query_cycle_active: Query<&CycleActive>,
+) {
+    for collider in query_added.iter() {
+        let inactive = std::iter::once(collider)
+            .chain(query_parents.iter_ancestors(collider))
+            .any(|entity| query_cycle_active.get(entity).is_ok_and(|active| !active.0));
+        if inactive {
+            commands.entity(collider).insert(ColliderDisabled);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cycles(filter: &str) -> Vec<usize> {
+        let filter: CycleFilter = filter.parse().unwrap();
+        (1..=6).filter(|&cycle| filter.contains(cycle)).collect()
+    }
+
+    #[test]
+    fn parses_odd_and_even() {
+        assert_eq!(cycles("odd"), [1, 3, 5]);
+        assert_eq!(cycles("Even"), [2, 4, 6]);
+    }
+
+    #[test]
+    fn parses_numbers_and_ranges() {
+        assert_eq!(cycles("3"), [3]);
+        assert_eq!(cycles("4+"), [4, 5, 6]);
+        assert_eq!(cycles("<3"), [1, 2]);
+        assert_eq!(cycles(" 1, 5+ "), [1, 5, 6]);
+    }
+
+    #[test]
+    fn rejects_invalid_terms() {
+        for filter in ["", "thrice", "2-4", "+", "<", "odd,", "-1"] {
+            assert!(
+                matches!(
+                    filter.parse::<CycleFilter>(),
+                    Err(CycleFilterError::InvalidTerm(_))
+                ),
+                "{filter:?} should be invalid"
+            );
+        }
+    }
+}
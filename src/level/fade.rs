@@ -0,0 +1,113 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::Velocity;
+
+use crate::{
+    camera::{CameraPanning, CameraPanningState},
+    entities::trigger::TriggerArea,
+    menus::DeathCount,
+    player::{PlayerCheckpoint, PlayerMarker, PlayerStatus},
+    state::{LevelLoadingState, TargetLevel, TargetPack},
+};
+
+use super::{manifest::LevelManifest, FromLevelSelect, LastAccessibleLevel};
+
+/// Fade at which the screen is black long enough to respawn the player or leave the level
+const FULL_FADE: f32 = 1.5;
+/// How much the fade changes per second
+const FADE_SPEED: f32 = 2.;
+
+/// How far the screen has faded to black after the player died, finished or left the level,
+/// from 0 to [`FULL_FADE`]. Anything past 1 is fully black.
+#[derive(Resource, Default)]
+pub struct ScreenFade(pub f32);
+
+/// Sent when a dead player is moved back to their checkpoint
+#[derive(Event)]
+pub struct PlayerRespawnedEvent {
+    /// Where the player died
+    pub from: Vec3,
+}
+
+/// Fades the screen out while the player is dead, finished or leaving, and respawns the player or
+/// moves on once it's black
+pub fn fade_screen(
+    mut screen_fade: ResMut<ScreenFade>,
+    mut camera_panning_state: ResMut<CameraPanning>,
+    mut query_player: Query<
+        (
+            &mut PlayerStatus,
+            &PlayerCheckpoint,
+            &mut Transform,
+            &mut Velocity,
+        ),
+        With<PlayerMarker>,
+    >,
+    mut query_triggers: Query<(&mut TriggerArea, &mut Visibility)>,
+    mut next_state: ResMut<NextState<LevelLoadingState>>,
+    target_pack: Res<TargetPack>,
+    mut target_level: ResMut<TargetLevel>,
+    level_manifest: Res<LevelManifest>,
+    time: Res<Time>,
+    mut from_level_select: ResMut<FromLevelSelect>,
+    mut last_accessible_level: ResMut<LastAccessibleLevel>,
+    mut death_count: ResMut<DeathCount>,
+    mut respawned_events: EventWriter<PlayerRespawnedEvent>,
+) {
+    let Ok((mut player_status, player_checkpoint, mut player_transform, mut player_velocity)) =
+        query_player.get_single_mut()
+    else {
+        return;
+    };
+
+    if !(player_status.level_finished || player_status.dead || player_status.exiting) {
+        screen_fade.0 = (screen_fade.0 - time.delta_seconds() * FADE_SPEED).max(0.);
+        return;
+    }
+    screen_fade.0 += time.delta_seconds() * FADE_SPEED;
+    if player_status.dead {
+        *player_velocity = Velocity::zero();
+    }
+    if screen_fade.0 < FULL_FADE {
+        return;
+    }
+    screen_fade.0 = FULL_FADE;
+
+    if player_status.level_finished {
+        let pack = level_manifest.packs.get(target_pack.0);
+        if let Some(pack) = pack {
+            last_accessible_level.unlock(&pack.id, target_level.0 + 1);
+        }
+        if from_level_select.0 {
+            from_level_select.0 = false;
+            next_state.set(LevelLoadingState::LevelSelect);
+        } else {
+            target_level.0 += 1;
+            if target_level.0 >= pack.map_or(0, |pack| pack.levels.len()) {
+                next_state.set(LevelLoadingState::EndScreen);
+            } else {
+                next_state.set(LevelLoadingState::Loading);
+                camera_panning_state.panning_state = CameraPanningState::PanningToGoal;
+            }
+        }
+    } else if player_status.exiting {
+        if from_level_select.0 {
+            from_level_select.0 = false;
+            next_state.set(LevelLoadingState::LevelSelect);
+        } else {
+            next_state.set(LevelLoadingState::MainMenu);
+        }
+    } else {
+        death_count.0 += 1;
+        player_status.dead = false;
+        for (mut trigger, mut visibility) in query_triggers.iter_mut() {
+            if !trigger.active() {
+                trigger.respawn();
+                *visibility = Visibility::Inherited;
+            }
+        }
+        respawned_events.send(PlayerRespawnedEvent {
+            from: player_transform.translation,
+        });
+        player_transform.translation = player_checkpoint.transform.extend(0.);
+    }
+}
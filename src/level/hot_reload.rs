@@ -0,0 +1,90 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::camera::{CameraMarker, ParallaxCoefficient, PlayerCameraMarker};
+use crate::player::{PlayerCheckpoint, PlayerMarker};
+
+/// Where the player was when the LDtk project of the current level changed on disk. bevy_ecs_ldtk
+/// respawns the level in place, which only happens with the `hot_reload` feature watching files.
+#[derive(Resource, Default)]
+pub(super) struct PendingReload(Option<ReloadedPlayer>);
+
+#[derive(Clone, Copy)]
+pub(super) struct ReloadedPlayer {
+    translation: Vec2,
+    checkpoint: Vec2,
+}
+
+pub(super) fn detect_level_reload(
+    mut ldtk_project_events: EventReader<AssetEvent<LdtkProject>>,
+    query_ldtk_world: Query<&Handle<LdtkProject>>,
+    query_player: Query<(&Transform, &PlayerCheckpoint), With<PlayerMarker>>,
+    mut pending_reload: ResMut<PendingReload>,
+) {
+    let Ok(ldtk_handle) = query_ldtk_world.get_single() else {
+        return;
+    };
+    let mut modified = false;
+    for event in ldtk_project_events.read() {
+        modified |= event.is_modified(ldtk_handle);
+    }
+    if !modified {
+        return;
+    }
+    let Ok((transform, checkpoint)) = query_player.get_single() else {
+        return;
+    };
+    info!("level changed on disk, reloading");
+    pending_reload.0 = Some(ReloadedPlayer {
+        translation: transform.translation.xy(),
+        checkpoint: checkpoint.transform,
+    });
+}
+
+/// Moves the player of a reloaded level back to their checkpoint, taking the cameras along the
+/// same way respawning after a death does
+pub(super) fn restore_reloaded_player(
+    mut query_player: Query<(&mut Transform, &mut PlayerCheckpoint), Added<PlayerMarker>>,
+    mut query_player_camera: Query<
+        &mut Transform,
+        (With<PlayerCameraMarker>, Without<PlayerMarker>),
+    >,
+    mut query_cameras: Query<
+        (&mut Transform, &ParallaxCoefficient),
+        (
+            With<CameraMarker>,
+            Without<PlayerMarker>,
+            Without<PlayerCameraMarker>,
+        ),
+    >,
+    mut pending_reload: ResMut<PendingReload>,
+) {
+    let Ok((mut player_transform, mut player_checkpoint)) = query_player.get_single_mut() else {
+        return;
+    };
+    let Some(reloaded_player) = pending_reload.0.take() else {
+        return;
+    };
+
+    player_checkpoint.transform = reloaded_player.checkpoint;
+    player_transform.translation.x = reloaded_player.checkpoint.x;
+    player_transform.translation.y = reloaded_player.checkpoint.y;
+
+    let Ok(mut player_camera_transform) = query_player_camera.get_single_mut() else {
+        return;
+    };
+
+    let delta = (reloaded_player.checkpoint - reloaded_player.translation).extend(0.);
+    let camera_offset =
+        (reloaded_player.translation.x - player_camera_transform.translation.x).min(0.);
+    player_camera_transform.translation += delta;
+    player_camera_transform.translation.x += camera_offset;
+    for (mut camera_transform, parallax_coefficient) in query_cameras.iter_mut() {
+        camera_transform.translation += parallax_coefficient.0 * delta;
+        camera_transform.translation.x += parallax_coefficient.0 * camera_offset;
+    }
+}
+
+pub(super) fn discard_pending_reload(mut pending_reload: ResMut<PendingReload>) {
+    pending_reload.0 = None;
+}
@@ -0,0 +1,100 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+/// Path of the level manifest, relative to the assets folder
+pub const LEVEL_MANIFEST_PATH: &str = "levels.manifest.ron";
+
+/// Every level pack in the game, each with its levels in the order they are played.
+///
+/// Loaded from [`LEVEL_MANIFEST_PATH`] and mirrored into a resource of the same type once it has
+/// loaded, so systems can read it with `Res<LevelManifest>`.
+#[derive(Asset, Resource, TypePath, Deserialize, Debug, Clone, Default)]
+pub struct LevelManifest {
+    pub packs: Vec<LevelPack>,
+}
+
+impl LevelManifest {
+    pub fn level(&self, pack: usize, level: usize) -> Option<(&LevelPack, &LevelManifestEntry)> {
+        let pack = self.packs.get(pack)?;
+        Some((pack, pack.levels.get(level)?))
+    }
+}
+
+/// A group of levels that all live in the same LDtk project
+#[derive(Deserialize, Debug, Clone)]
+pub struct LevelPack {
+    /// Stable identifier used to track progress through the pack
+    pub id: String,
+    pub name: String,
+    /// Path of the LDtk project containing the pack's levels, relative to the assets folder
+    pub project: String,
+    pub levels: Vec<LevelManifestEntry>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct LevelManifestEntry {
+    pub iid: String,
+    pub name: String,
+}
+
+#[derive(Resource)]
+pub struct LevelManifestHandle(pub Handle<LevelManifest>);
+
+#[derive(Default)]
+pub struct LevelManifestLoader;
+
+#[derive(Debug, Error)]
+pub enum LevelManifestLoaderError {
+    #[error("could not read level manifest: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse level manifest: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+impl AssetLoader for LevelManifestLoader {
+    type Asset = LevelManifest;
+    type Settings = ();
+    type Error = LevelManifestLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["manifest.ron"]
+    }
+}
+
+pub fn load_level_manifest(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(LevelManifestHandle(asset_server.load(LEVEL_MANIFEST_PATH)));
+}
+
+/// Copies the manifest into the [`LevelManifest`] resource whenever it (re)loads
+pub fn update_level_manifest(
+    mut commands: Commands,
+    mut manifest_events: EventReader<AssetEvent<LevelManifest>>,
+    manifest_handle: Res<LevelManifestHandle>,
+    manifest_assets: Res<Assets<LevelManifest>>,
+) {
+    for event in manifest_events.read() {
+        if !event.is_loaded_with_dependencies(&manifest_handle.0)
+            && !event.is_modified(&manifest_handle.0)
+        {
+            continue;
+        }
+        if let Some(manifest) = manifest_assets.get(&manifest_handle.0) {
+            commands.insert_resource(manifest.clone());
+        }
+    }
+}
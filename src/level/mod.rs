@@ -1,70 +1,296 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, time::Stopwatch};
 use bevy_ecs_ldtk::prelude::*;
 use bevy_ecs_tilemap::tiles::TileTextureIndex;
 use bevy_rapier2d::prelude::*;
+use cycles::{apply_cycle_filters, attach_cycle_filters, disable_added_colliders};
+use fade::{fade_screen, PlayerRespawnedEvent, ScreenFade};
+use hot_reload::{
+    detect_level_reload, discard_pending_reload, restore_reloaded_player, PendingReload,
+};
+use manifest::{load_level_manifest, update_level_manifest, LevelManifest, LevelManifestLoader};
+use rand::Rng;
+use std::collections::HashMap;
 use tiles::spawn_wall_collision;
 
+pub mod cycles;
+pub mod fade;
+mod hot_reload;
+pub mod manifest;
 mod tiles;
 
 use crate::camera::{CameraPanning, CameraPanningState, PlayerCameraMarker};
-use crate::player::{PlayerMarker, PlayerStatus, SetCheckpointEvent};
-use crate::state::{LevelLoadingState, TargetLevel};
+use crate::menus::{CycleCount, DeathCount, SpeedrunTimer};
+use crate::physics::interpolate_translation;
+use crate::player::{
+    add_colliders, LevelCompletedEvent, PlayerMarker, PlayerStatus, SetCheckpointEvent,
+};
+use crate::replay::GameRng;
+use crate::state::{LevelLoadingState, TargetLevel, TargetPack};
 
 pub struct LevelManagementPlugin;
 
 impl Plugin for LevelManagementPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(LdtkPlugin)
+            .init_asset::<LevelManifest>()
+            .init_asset_loader::<LevelManifestLoader>()
+            .init_resource::<LevelManifest>()
             // .insert_resource(LevelSelection::index(0))
-            .insert_resource(LastAccessibleLevel(0))
+            .init_resource::<LastAccessibleLevel>()
+            .init_resource::<BestTimes>()
+            .init_resource::<LevelTimer>()
+            .init_resource::<RunSplits>()
+            .init_resource::<LevelStartCounts>()
+            .init_resource::<LevelWrap>()
+            .init_resource::<PendingReload>()
+            .init_resource::<ScreenFade>()
+            .init_resource::<CameraPanning>()
             .insert_resource(FromLevelSelect(false))
+            .insert_resource(SpeedrunTimer(Stopwatch::new()))
+            .insert_resource(DeathCount(0))
+            .insert_resource(CycleCount(0))
+            .insert_resource(LdtkSettings {
+                level_background: LevelBackground::Nonexistent,
+                ..default()
+            })
             .add_event::<SetCheckpointEvent>()
+            .add_event::<PlayerRespawnedEvent>()
             .register_ldtk_int_cell::<TerrainBundle>(1)
             .register_ldtk_int_cell::<WaterBundle>(2)
             .register_ldtk_int_cell::<SpikeBundle>(4)
-            .add_systems(Startup, spawn_ldtk_world)
-            .add_systems(Update, spawn_wall_collision)
+            .add_systems(Startup, load_level_manifest)
+            .add_systems(Update, update_level_manifest)
+            .add_systems(Update, spawn_wall_collision.after(read_level_wrap))
             .add_systems(Update, init_spike_textures)
-            .add_systems(OnEnter(LevelLoadingState::Loading), load_level)
+            .add_systems(Update, attach_cycle_filters)
+            .add_systems(Update, read_level_wrap)
+            .add_systems(
+                FixedUpdate,
+                (
+                    apply_cycle_filters.run_if(resource_changed::<CycleCount>),
+                    disable_added_colliders,
+                )
+                    .before(PhysicsSet::SyncBackend)
+                    .run_if(in_state(LevelLoadingState::Loaded)),
+            )
             .add_systems(
                 Update,
-                (inter_level_pause,).run_if(in_state(LevelLoadingState::Loading)),
+                (
+                    // retried every frame until the manifest has loaded, the timer marks it done
+                    load_level.run_if(not(any_with_component::<InterLevelTimer>)),
+                    inter_level_pause,
+                )
+                    .run_if(in_state(LevelLoadingState::Loading)),
             )
             .add_systems(
                 OnEnter(LevelLoadingState::Loaded),
                 (spawn_backwards_barrier,),
             )
-            .add_systems(OnExit(LevelLoadingState::Loaded), (cleanup_level_objects,))
             .add_systems(
                 Update,
-                (update_backwards_barrier)
+                (
+                    detect_level_reload,
+                    restore_reloaded_player.after(add_colliders),
+                )
+                    .run_if(in_state(LevelLoadingState::Loaded)),
+            )
+            .add_systems(
+                OnExit(LevelLoadingState::Loaded),
+                (cleanup_level_objects, discard_pending_reload),
+            )
+            .add_systems(
+                Update,
+                (update_backwards_barrier).run_if(in_state(LevelLoadingState::Loaded)),
+            )
+            .add_systems(
+                Update,
+                (
+                    tick_level_timer,
+                    record_split,
+                    fade_screen.after(interpolate_translation),
+                )
                     .run_if(in_state(LevelLoadingState::Loaded)),
             );
     }
 }
 
-#[derive(Resource)]
-pub struct LastAccessibleLevel(pub usize);
+/// The furthest level the player has unlocked in each level pack, keyed by pack id
+#[derive(Resource, Default)]
+pub struct LastAccessibleLevel(pub HashMap<String, usize>);
+
+impl LastAccessibleLevel {
+    pub fn get(&self, pack_id: &str) -> usize {
+        self.0.get(pack_id).copied().unwrap_or(0)
+    }
+
+    pub fn unlock(&mut self, pack_id: &str, level: usize) {
+        let last_accessible_level = self.0.entry(pack_id.to_string()).or_insert(0);
+        if *last_accessible_level < level {
+            *last_accessible_level = level;
+        }
+    }
+}
+
+/// Best completion time of each level in seconds, keyed by pack id and then by level iid
+#[derive(Resource, Default)]
+pub struct BestTimes(pub HashMap<String, HashMap<String, f32>>);
+
+impl BestTimes {
+    pub fn get(&self, pack_id: &str, level_iid: &str) -> Option<f32> {
+        self.0.get(pack_id)?.get(level_iid).copied()
+    }
+
+    /// Returns whether `time` is a new best for the level
+    pub fn record(&mut self, pack_id: &str, level_iid: &str, time: f32) -> bool {
+        let pack_times = self.0.entry(pack_id.to_string()).or_default();
+        match pack_times.get(level_iid) {
+            Some(best_time) if *best_time <= time => false,
+            _ => {
+                pack_times.insert(level_iid.to_string(), time);
+                true
+            }
+        }
+    }
+}
+
+/// In-game time spent in the current level
+#[derive(Resource, Default)]
+pub struct LevelTimer(pub Stopwatch);
+
+/// A level finished during the current run
+#[derive(Debug, Clone)]
+pub struct LevelSplit {
+    pub pack_id: String,
+    pub level_iid: String,
+    pub level_name: String,
+    /// In-game time spent in the level, in seconds
+    pub level_time: f32,
+    /// In-game time of the run when the level was finished, in seconds
+    pub run_time: f32,
+    pub deaths: usize,
+    pub cycles: usize,
+}
+
+/// The levels finished during the current run, in the order they were finished
+#[derive(Resource, Default)]
+pub struct RunSplits(pub Vec<LevelSplit>);
+
+/// Death and cycle counts when the current level was loaded, used to count them per level
+#[derive(Resource, Default)]
+struct LevelStartCounts {
+    deaths: usize,
+    cycles: usize,
+}
 
 #[derive(Resource)]
 pub struct FromLevelSelect(pub bool);
 
+/// LDtk level field picking how a level wraps around, an enum with the values `Horizontal`,
+/// `Vertical` and `Both`. Levels without it wrap horizontally.
+const WRAP_FIELD: &str = "wrap";
+
+/// The edges of a level the player and cameras wrap around
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WrapMode {
+    #[default]
+    Horizontal,
+    Vertical,
+    Both,
+}
+
+impl WrapMode {
+    pub fn horizontal(self) -> bool {
+        matches!(self, WrapMode::Horizontal | WrapMode::Both)
+    }
+
+    pub fn vertical(self) -> bool {
+        matches!(self, WrapMode::Vertical | WrapMode::Both)
+    }
+
+    /// The axis the camera scrolls along by itself, with the backwards barrier behind it: 0 for x
+    /// and 1 for y. Levels that wrap both ways scroll horizontally.
+    pub fn scroll_axis(self) -> usize {
+        match self {
+            WrapMode::Horizontal | WrapMode::Both => 0,
+            WrapMode::Vertical => 1,
+        }
+    }
+}
+
+/// The wrap mode of the current level
+#[derive(Resource, Default)]
+pub struct LevelWrap(pub WrapMode);
+
+/// Systems that depend on [`LevelWrap`] when a level spawns, like setting up the cameras, should
+/// run after this
+pub fn read_level_wrap(
+    query_level: Query<&LevelIid, Added<LevelIid>>,
+    query_ldtk_world: Query<&Handle<LdtkProject>>,
+    ldtk_projects: Res<Assets<LdtkProject>>,
+    mut level_wrap: ResMut<LevelWrap>,
+) {
+    let Ok(level_iid) = query_level.get_single() else {
+        return;
+    };
+    let mode = query_ldtk_world
+        .get_single()
+        .ok()
+        .and_then(|handle| ldtk_projects.get(handle))
+        .and_then(|project| project.get_raw_level_by_iid(level_iid.get()))
+        .and_then(|level| level.get_maybe_enum_field(WRAP_FIELD).ok())
+        .and_then(Option::as_ref);
+    level_wrap.0 = match mode.map(String::as_str) {
+        Some("Horizontal") | None => WrapMode::Horizontal,
+        Some("Vertical") => WrapMode::Vertical,
+        Some("Both") => WrapMode::Both,
+        Some(mode) => {
+            warn!("unknown wrap mode {mode}, the level will wrap horizontally");
+            WrapMode::Horizontal
+        }
+    };
+}
+
 #[derive(Component)]
 struct InterLevelTimer(Timer);
 
+/// Loads the target level, waiting in the loading state until the level manifest has it
 fn load_level(
     mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    target_pack: Res<TargetPack>,
     target_level: Res<TargetLevel>,
+    level_manifest: Res<LevelManifest>,
     mut last_accessible_level: ResMut<LastAccessibleLevel>,
-    mut query_level_set: Query<&mut LevelSet>,
+    mut level_timer: ResMut<LevelTimer>,
+    mut level_start_counts: ResMut<LevelStartCounts>,
+    death_count: Res<DeathCount>,
+    cycle_count: Res<CycleCount>,
+    mut query_ldtk_world: Query<(&mut Handle<LdtkProject>, &mut LevelSet)>,
 ) {
+    let Some((pack, level)) = level_manifest.level(target_pack.0, target_level.0) else {
+        return;
+    };
     commands.spawn(InterLevelTimer(Timer::from_seconds(0.7, TimerMode::Once)));
-    if let Ok(mut level_set) = query_level_set.get_single_mut() {
-        *level_set = LevelSet::from_iids([LEVEL_IIDS[target_level.0]]);
-        if last_accessible_level.0 < target_level.0 {
-            last_accessible_level.0 = target_level.0;
+    level_timer.0.reset();
+    *level_start_counts = LevelStartCounts {
+        deaths: death_count.0,
+        cycles: cycle_count.0,
+    };
+    let ldtk_handle = asset_server.load(pack.project.clone());
+    let level_set = LevelSet::from_iids([level.iid.clone()]);
+    if let Ok((mut world_ldtk_handle, mut world_level_set)) = query_ldtk_world.get_single_mut() {
+        if *world_ldtk_handle != ldtk_handle {
+            *world_ldtk_handle = ldtk_handle;
         }
+        *world_level_set = level_set;
+    } else {
+        commands.spawn(LdtkWorldBundle {
+            ldtk_handle,
+            level_set,
+            ..default()
+        });
     }
+    last_accessible_level.unlock(&pack.id, target_level.0);
 }
 
 fn inter_level_pause(
@@ -84,36 +310,66 @@ fn inter_level_pause(
     timer.0.tick(time.delta());
 }
 
-fn cleanup_level_objects(
-    query: Query<Entity, Or<(With<LevelIid>, With<BackwardsBarrier>)>>,
-    mut commands: Commands,
+/// The in-game timers only run while the player is in control of an unfinished level, so the
+/// pause between levels, the intro camera pan and the fade out after the goal are not counted
+fn tick_level_timer(
+    time: Res<Time>,
+    camera_panning_state: Res<CameraPanning>,
+    query_player: Query<&PlayerStatus, With<PlayerMarker>>,
+    mut level_timer: ResMut<LevelTimer>,
+    mut speedrun_timer: ResMut<SpeedrunTimer>,
 ) {
-    for entity in query.iter() {
-        commands.entity(entity).despawn_recursive();
+    let Ok(player_status) = query_player.get_single() else {
+        return;
+    };
+    if camera_panning_state.panning_state != CameraPanningState::WaitingAtPlayer
+        || player_status.level_finished
+    {
+        return;
     }
+    level_timer.0.tick(time.delta());
+    speedrun_timer.0.tick(time.delta());
 }
 
-pub const LEVEL_IIDS: [&str; 6] = [
-    "584033f0-25d0-11ef-8b42-1596277d2df3",
-    "410524d0-25d0-11ef-b3d7-db494d819bf6",
-    "a56e81e0-25d0-11ef-a5a2-a938910d70c0",
-    "dd650080-25d0-11ef-814d-6b1968b17386",
-    "a4a8aaa0-25d0-11ef-8b42-cbb4af80c537",
-    "d509f450-4ce0-11ef-bfc4-339ca4a6f158",
-];
-
-fn spawn_ldtk_world(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
+fn record_split(
+    mut level_completed_events: EventReader<LevelCompletedEvent>,
+    level_timer: Res<LevelTimer>,
+    speedrun_timer: Res<SpeedrunTimer>,
+    level_start_counts: Res<LevelStartCounts>,
+    death_count: Res<DeathCount>,
+    cycle_count: Res<CycleCount>,
+    target_pack: Res<TargetPack>,
     target_level: Res<TargetLevel>,
+    level_manifest: Res<LevelManifest>,
+    mut best_times: ResMut<BestTimes>,
+    mut run_splits: ResMut<RunSplits>,
 ) {
-    commands.spawn(LdtkWorldBundle {
-        ldtk_handle: asset_server.load("level.ldtk"),
-        level_set: LevelSet::from_iids([LEVEL_IIDS[target_level.0]]),
-        ..default()
-    });
+    for LevelCompletedEvent in level_completed_events.read() {
+        let Some((pack, level)) = level_manifest.level(target_pack.0, target_level.0) else {
+            continue;
+        };
+        let level_time = level_timer.0.elapsed_secs();
+        best_times.record(&pack.id, &level.iid, level_time);
+        run_splits.0.push(LevelSplit {
+            pack_id: pack.id.clone(),
+            level_iid: level.iid.clone(),
+            level_name: level.name.clone(),
+            level_time,
+            run_time: speedrun_timer.0.elapsed_secs(),
+            deaths: death_count.0.saturating_sub(level_start_counts.deaths),
+            cycles: cycle_count.0.saturating_sub(level_start_counts.cycles),
+        });
+    }
 }
 
+fn cleanup_level_objects(
+    query: Query<Entity, Or<(With<LevelIid>, With<BackwardsBarrier>)>>,
+    mut commands: Commands,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
 
 #[derive(Default, Component)]
 pub struct KillPlayerMarker;
@@ -164,17 +420,18 @@ impl Default for SpikeBundle {
             kill_player_marker: KillPlayerMarker,
             collider: Collider::cuboid(4.5, 4.5),
             sensor: Sensor,
-            info: SpikeInfo {
-                is_blue: rand::random::<f32>() < 0.2,
-            },
+            // picked in init_spike_textures, where the game's rng is available
+            info: SpikeInfo { is_blue: false },
         }
     }
 }
 
 pub fn init_spike_textures(
-    mut q_spikes: Query<(&SpikeInfo, &mut TileTextureIndex), Added<SpikeMarker>>,
+    mut q_spikes: Query<(&mut SpikeInfo, &mut TileTextureIndex), Added<SpikeMarker>>,
+    mut game_rng: ResMut<GameRng>,
 ) {
-    for (spike_info, mut tile_index) in q_spikes.iter_mut() {
+    for (mut spike_info, mut tile_index) in q_spikes.iter_mut() {
+        spike_info.is_blue = game_rng.0.gen::<f32>() < 0.2;
         if spike_info.is_blue {
             *tile_index = TileTextureIndex(tile_index.0 + 1);
         }
@@ -204,19 +461,27 @@ impl Default for TerrainBundle {
 #[derive(Component)]
 pub struct BackwardsBarrier;
 
+/// Spawns the barrier that keeps the player from going back past the edge of the screen, across
+/// the axis the level scrolls along
 fn spawn_backwards_barrier(
     mut commands: Commands,
     query_level: Query<&LayerMetadata, With<LayerMetadata>>,
+    level_wrap: Res<LevelWrap>,
 ) {
-    let mut level_height = 0.;
+    let mut level_size = Vec2::ZERO;
     for level in query_level.iter() {
         if level.layer_instance_type == bevy_ecs_ldtk::ldtk::Type::IntGrid {
-            level_height = level.c_hei as f32 * 16.;
+            level_size = Vec2::new(level.c_wid as f32, level.c_hei as f32) * 16.;
         }
     }
 
+    let collider = if level_wrap.0.scroll_axis() == 0 {
+        Collider::cuboid(1., level_size.y)
+    } else {
+        Collider::cuboid(level_size.x, 1.)
+    };
     commands
-        .spawn((Collider::cuboid(1., level_height), BackwardsBarrier))
+        .spawn((collider, BackwardsBarrier))
         .insert(TransformBundle::from_transform(Transform::from_xyz(
             0., 0., 0.,
         )));
@@ -230,6 +495,7 @@ fn update_backwards_barrier(
         (With<PlayerCameraMarker>, Without<BackwardsBarrier>),
     >,
     mut query_barrier: Query<&mut Transform, With<BackwardsBarrier>>,
+    level_wrap: Res<LevelWrap>,
 ) {
     if camera_panning_state.panning_state != CameraPanningState::WaitingAtPlayer {
         return;
@@ -241,33 +507,35 @@ fn update_backwards_barrier(
         return;
     };
 
-    let mut level_width = 0.;
+    let mut level_size = Vec2::ZERO;
     for level in query_level.iter() {
         if level.layer_instance_type == bevy_ecs_ldtk::ldtk::Type::IntGrid {
-            level_width = level.c_wid as f32 * 16.;
+            level_size = Vec2::new(level.c_wid as f32, level.c_hei as f32) * 16.;
         }
     }
 
-    let w_end = camera
+    let screen_br = camera
         .viewport_to_world_2d(
             camera_global_transform,
             camera.logical_viewport_size().unwrap(),
         )
-        .unwrap()
-        .x;
-    let w_start = camera
+        .unwrap();
+    let screen_tl = camera
         .viewport_to_world_2d(camera_global_transform, Vec2::new(0., 0.))
-        .unwrap()
-        .x;
-    let width = w_end - w_start;
+        .unwrap();
+    let screen_size = (screen_br - screen_tl).abs();
 
+    // behind the left edge of the screen, or the bottom edge in levels that scroll vertically
+    let axis = level_wrap.0.scroll_axis();
+    let level_length = level_size[axis];
     let barrier_offset = 5.;
     let barrier_jitter_correction = 10.;
-    barrier.translation.x = camera_transform.translation.x - width / 2. - barrier_offset;
-    if barrier.translation.x < 0. {
-        barrier.translation.x += level_width;
+    let mut position = camera_transform.translation[axis] - screen_size[axis] / 2. - barrier_offset;
+    if position < 0. {
+        position += level_length;
     }
-    if barrier.translation.x > level_width - barrier_offset - barrier_jitter_correction {
-        barrier.translation.x = level_width - barrier_offset - barrier_jitter_correction
+    if position > level_length - barrier_offset - barrier_jitter_correction {
+        position = level_length - barrier_offset - barrier_jitter_correction
     }
+    barrier.translation[axis] = position;
 }
@@ -3,7 +3,10 @@ use bevy_ecs_ldtk::prelude::*;
 use bevy_rapier2d::prelude::*;
 use std::collections::{HashMap, HashSet};
 
-use super::TerrainMarker;
+use super::{LevelWrap, TerrainMarker};
+
+/// How many tiles of wall are mirrored past each edge a level wraps around
+const SEAM_MIRROR_TILES: i32 = 2;
 
 /// NOTE: copied from https://github.com/Trouv/bevy_ecs_ldtk/blob/v0.10.0/examples/platformer/main.rs
 ///
@@ -23,6 +26,10 @@ use super::TerrainMarker;
 /// 2. combine wall tiles into flat "plates" in each individual row
 /// 3. combine the plates into rectangles across multiple rows wherever possible
 /// 4. spawn colliders for each rectangle
+///
+/// Along the edges a level wraps around, the walls on the other side of the seam are mirrored
+/// into a few extra tiles, so the ground and walls continue across the seam instead of ending at
+/// the edge the player is looped back at.
 pub fn spawn_wall_collision(
     mut commands: Commands,
     wall_query: Query<(&GridCoords, &Parent), Added<TerrainMarker>>,
@@ -30,6 +37,7 @@ pub fn spawn_wall_collision(
     level_query: Query<(Entity, &LevelIid)>,
     ldtk_projects: Query<&Handle<LdtkProject>>,
     ldtk_project_assets: Res<Assets<LdtkProject>>,
+    level_wrap: Res<LevelWrap>,
 ) {
     /// Represents a wide wall that is 1 tile tall
     /// Used to spawn wall collisions
@@ -51,28 +59,35 @@ pub fn spawn_wall_collision(
         // Consider where the walls are
         // storing them as GridCoords in a HashSet for quick, easy lookup
         //
-        // The key of this map will be the entity of the level the wall belongs to.
+        // The key of this map will be the entity of the layer the wall belongs to, which is an
+        // intgrid tile's direct parent.
         // This has two consequences in the resulting collision entities:
-        // 1. it forces the walls to be split along level boundaries
-        // 2. it lets us easily add the collision entities as children of the appropriate level entity
-        let mut level_to_wall_locations: HashMap<Entity, HashSet<GridCoords>> = HashMap::new();
+        // 1. it forces the walls to be split along level and layer boundaries
+        // 2. it lets us easily add the collision entities as children of the appropriate layer
+        //    entity, so layers that only exist in some cycles can disable them
+        let mut layer_to_wall_locations: HashMap<Entity, HashSet<GridCoords>> = HashMap::new();
 
         wall_query.iter().for_each(|(&grid_coords, parent)| {
-            // An intgrid tile's direct parent will be a layer entity, not the level entity
-            // To get the level entity, you need the tile's grandparent.
-            // This is where parent_query comes in.
-            if let Ok(grandparent) = parent_query.get(parent.get()) {
-                level_to_wall_locations
-                    .entry(grandparent.get())
-                    .or_default()
-                    .insert(grid_coords);
-            }
+            layer_to_wall_locations
+                .entry(parent.get())
+                .or_default()
+                .insert(grid_coords);
         });
 
-        level_query.iter().for_each(|(level_entity, level_iid)| {
-            if let Some(level_walls) = level_to_wall_locations.get(&level_entity) {
-                let ldtk_project = ldtk_project_assets
-                    .get(ldtk_projects.single())
+        for (&layer_entity, level_walls) in layer_to_wall_locations.iter() {
+            // To get the level entity, you need the layer's parent.
+            // This is where parent_query comes in.
+            if let Ok((level_entity, level_iid)) = parent_query
+                .get(layer_entity)
+                .and_then(|level| level_query.get(level.get()))
+            {
+                // A level's parent is the world entity it was spawned from,
+                // which holds the handle of the project it belongs to.
+                let ldtk_project = parent_query
+                    .get(level_entity)
+                    .and_then(|world| ldtk_projects.get(world.get()))
+                    .ok()
+                    .and_then(|handle| ldtk_project_assets.get(handle))
                     .expect("Project should be loaded if level has spawned");
 
                 let level = ldtk_project
@@ -87,16 +102,36 @@ pub fn spawn_wall_collision(
                     ..
                 } = level.layer_instances()[0];
 
+                let margin_x = if level_wrap.0.horizontal() {
+                    SEAM_MIRROR_TILES
+                } else {
+                    0
+                };
+                let margin_y = if level_wrap.0.vertical() {
+                    SEAM_MIRROR_TILES
+                } else {
+                    0
+                };
+                // tiles in the margins are the walls across the seam
+                let is_wall = |x: i32, y: i32| {
+                    x < width + margin_x
+                        && y < height + margin_y
+                        && level_walls.contains(&GridCoords {
+                            x: x.rem_euclid(width),
+                            y: y.rem_euclid(height),
+                        })
+                };
+
                 // combine wall tiles into flat "plates" in each individual row
                 let mut plate_stack: Vec<Vec<Plate>> = Vec::new();
 
-                for y in 0..height {
+                for y in -margin_y..height + margin_y {
                     let mut row_plates: Vec<Plate> = Vec::new();
                     let mut plate_start = None;
 
                     // + 1 to the width so the algorithm "terminates" plates that touch the right edge
-                    for x in 0..width + 1 {
-                        match (plate_start, level_walls.contains(&GridCoords { x, y })) {
+                    for x in -margin_x..width + margin_x + 1 {
+                        match (plate_start, is_wall(x, y)) {
                             (Some(s), false) => {
                                 row_plates.push(Plate {
                                     left: s,
@@ -121,6 +156,7 @@ pub fn spawn_wall_collision(
                 plate_stack.push(Vec::new());
 
                 for (y, current_row) in plate_stack.into_iter().enumerate() {
+                    let y = y as i32 - margin_y;
                     for prev_plate in &prev_row {
                         if !current_row.contains(prev_plate) {
                             // remove the finished rect so that the same plate in the future starts a new rect
@@ -134,8 +170,8 @@ pub fn spawn_wall_collision(
                             .entry(plate.clone())
                             .and_modify(|e| e.top += 1)
                             .or_insert(Rect {
-                                bottom: y as i32,
-                                top: y as i32,
+                                bottom: y,
+                                top: y,
                                 left: plate.left,
                                 right: plate.right,
                             });
@@ -143,13 +179,13 @@ pub fn spawn_wall_collision(
                     prev_row = current_row;
                 }
 
-                commands.entity(level_entity).with_children(|level| {
+                commands.entity(layer_entity).with_children(|layer| {
                     // Spawn colliders for every rectangle..
-                    // Making the collider a child of the level serves two purposes:
+                    // Making the collider a child of the layer serves two purposes:
                     // 1. Adjusts the transforms to be relative to the level for free
                     // 2. the colliders will be despawned automatically when levels unload
                     for wall_rect in wall_rects {
-                        level
+                        layer
                             .spawn_empty()
                             .insert(Collider::cuboid(
                                 (wall_rect.right as f32 - wall_rect.left as f32 + 1.)
@@ -172,6 +208,6 @@ pub fn spawn_wall_collision(
                     }
                 });
             }
-        });
+        }
     }
 }
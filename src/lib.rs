@@ -0,0 +1,164 @@
+//! Dungeon Deja Vu as a library, so level tools, bots and tests can run the game in their own
+//! [`App`]. The `cyclic-tower` binary is just [`GamePlugin`] with diagnostics.
+
+pub mod args;
+pub mod autosplitter;
+pub mod camera;
+pub mod echo;
+pub mod entities;
+pub mod ghost;
+pub mod headless;
+pub mod input;
+pub mod level;
+pub mod menus;
+pub mod physics;
+pub mod player;
+pub mod replay;
+pub mod save;
+pub mod sound_effects;
+pub mod splits;
+pub mod state;
+
+use autosplitter::AutosplitterPlugin;
+use bevy::{
+    app::PluginGroupBuilder,
+    asset::AssetMetaCheck,
+    input::InputPlugin,
+    log::{Level, LogPlugin},
+    prelude::*,
+    state::app::StatesPlugin,
+    window::PresentMode,
+};
+use camera::CameraManagementPlugin;
+use echo::EchoManagementPlugin;
+use entities::EntityManagementPlugin;
+use ghost::GhostManagementPlugin;
+use headless::HeadlessPlugin;
+use input::InputManagementPlugin;
+use level::LevelManagementPlugin;
+use menus::MenuManagementPlugin;
+use physics::PhysicsManagementPlugin;
+use player::PlayerManagementPlugin;
+use replay::ReplayManagementPlugin;
+use save::SaveManagementPlugin;
+use sound_effects::SoundEffectsManagementPlugin;
+use splits::SplitsManagementPlugin;
+use state::{StartingLevel, StateManagementPlugin};
+
+/// The whole game, including Bevy's own plugins. Individual plugins can still be swapped out
+/// with [`PluginGroupBuilder::set`], e.g. to point the replay or save plugins elsewhere.
+pub struct GamePlugin {
+    /// Runs without a window, renderer, audio, menus, HUD or save files, for tests and bots that
+    /// step the [`App`] themselves
+    pub headless: bool,
+    /// Level to start on instead of the main menu
+    pub starting_level: Option<StartingLevel>,
+    /// Folder assets are loaded from, relative to the crate or executable
+    pub asset_root: String,
+    /// Size of the window in logical pixels, instead of Bevy's default
+    pub window_size: Option<UVec2>,
+    /// Waits for the display's refresh before presenting each frame
+    pub vsync: bool,
+    /// Starts with the music and sound effects muted
+    pub muted: bool,
+    /// Draws every collider. Ignored when headless.
+    pub debug_physics: bool,
+    /// Least severe log messages that are printed
+    pub log_level: Level,
+}
+
+impl Default for GamePlugin {
+    fn default() -> Self {
+        Self {
+            headless: false,
+            starting_level: None,
+            asset_root: "assets".into(),
+            window_size: None,
+            vsync: false,
+            muted: false,
+            debug_physics: false,
+            log_level: Level::ERROR,
+        }
+    }
+}
+
+impl PluginGroup for GamePlugin {
+    fn build(self) -> PluginGroupBuilder {
+        let asset_plugin = AssetPlugin {
+            file_path: self.asset_root,
+            meta_check: AssetMetaCheck::Never,
+            ..default()
+        };
+        let group = PluginGroupBuilder::start::<Self>();
+        let group = if self.headless {
+            group
+                .add_group(MinimalPlugins)
+                .add(asset_plugin)
+                .add(ImagePlugin::default_nearest())
+                .add(TransformPlugin)
+                .add(HierarchyPlugin)
+                .add(StatesPlugin)
+                .add(InputPlugin)
+                .add(HeadlessPlugin)
+        } else {
+            let mut window = Window {
+                title: "Dungeon Deja Vu".into(),
+                name: Some("dungeon.dejavu".into()),
+                present_mode: if self.vsync {
+                    PresentMode::AutoVsync
+                } else {
+                    PresentMode::AutoNoVsync
+                },
+                fit_canvas_to_parent: true,
+                prevent_default_event_handling: false,
+                ..default()
+            };
+            if let Some(window_size) = self.window_size {
+                window.resolution = window_size.as_vec2().into();
+            }
+            group.add_group(
+                DefaultPlugins
+                    .set(ImagePlugin::default_nearest())
+                    .set(asset_plugin)
+                    .set(LogPlugin {
+                        level: self.log_level,
+                        ..default()
+                    })
+                    .set(WindowPlugin {
+                        primary_window: Some(window),
+                        ..default()
+                    }),
+            )
+        };
+
+        let group = group
+            .add(PhysicsManagementPlugin {
+                debug_render: self.debug_physics && !self.headless,
+            })
+            .add(InputManagementPlugin)
+            .add(LevelManagementPlugin)
+            .add(StateManagementPlugin {
+                starting_level: self.starting_level,
+            })
+            .add(EntityManagementPlugin)
+            .add(PlayerManagementPlugin)
+            // headless runs are scripted, so they leave the player's saves alone
+            .add(if self.headless {
+                SaveManagementPlugin { directory: None }
+            } else {
+                SaveManagementPlugin::default()
+            })
+            .add(ReplayManagementPlugin { replay_path: None })
+            .add(EchoManagementPlugin);
+        if self.headless {
+            return group;
+        }
+        group
+            .add(CameraManagementPlugin)
+            .add(MenuManagementPlugin)
+            .add(SoundEffectsManagementPlugin { muted: self.muted })
+            .add(SplitsManagementPlugin)
+            .add(AutosplitterPlugin { address: None })
+            .add(GhostManagementPlugin)
+    }
+}
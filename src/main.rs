@@ -1,24 +1,20 @@
-mod camera;
-mod entities;
-mod level;
-mod menus;
-mod player;
-mod sound_effects;
-mod state;
-
 use bevy::{
-    asset::AssetMetaCheck, diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin}, ecs::schedule::{LogLevel, ScheduleBuildSettings}, log::LogPlugin, prelude::*, window::PresentMode
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    prelude::*,
+};
+use cyclic_tower::{
+    args::ArgsError, autosplitter::AutosplitterPlugin, replay::ReplayManagementPlugin, GamePlugin,
 };
-use bevy_rapier2d::prelude::*;
-use camera::CameraManagementPlugin;
-use entities::EntityManagementPlugin;
-use level::LevelManagementPlugin;
-use menus::MenuManagementPlugin;
-use player::PlayerManagementPlugin;
-use sound_effects::SoundEffectsManagementPlugin;
-use state::StateManagementPlugin;
 
-fn main() {
+fn main() -> AppExit {
+    let game = match parse_args() {
+        Ok(game) => game,
+        Err(e) => {
+            eprintln!("{e}");
+            return AppExit::error();
+        }
+    };
+
     App::new()
         // Enable ambiguity warnings for the Update schedule
         // .edit_schedule(Startup, |schedule| {
@@ -28,47 +24,16 @@ fn main() {
         //     });
         // })
         .add_plugins((
-            DefaultPlugins
-                .set(ImagePlugin::default_nearest())
-                .set(AssetPlugin {
-                    meta_check: AssetMetaCheck::Never,
-                    ..default()
-                })
-                .set(LogPlugin {
-                    level: bevy::log::Level::ERROR,
-                    ..default()
-                })
-                .set(WindowPlugin {
-                    primary_window: Some(Window {
-                        title: "Dungeon Deja Vu".into(),
-                        name: Some("dungeon.dejavu".into()),
-                        // resolution: (500., .).into(),
-                        present_mode: PresentMode::AutoNoVsync,
-                        fit_canvas_to_parent: true,
-                        prevent_default_event_handling: false,
-                        ..default()
-                    }),
-                    ..default()
-                }),
+            game,
             LogDiagnosticsPlugin::default(),
             FrameTimeDiagnosticsPlugin,
         ))
-        .add_plugins(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(24.))
-        // .add_plugins(RapierDebugRenderPlugin::default())
-        .add_plugins((
-            CameraManagementPlugin,
-            LevelManagementPlugin,
-            StateManagementPlugin,
-            EntityManagementPlugin,
-            PlayerManagementPlugin,
-            MenuManagementPlugin,
-            SoundEffectsManagementPlugin,
-        )
-    ).run();
+        .run()
 }
 
-// fn setup(
-//     mut rapier_config: ResMut<RapierConfiguration>,
-// ){
-//     rapier_config.timestep_mode = TimestepMode::Fixed { dt: 1./60., substeps:1};
-// }.add_systems(Startup, setup)
+/// Configures the game and the plugins reading their own flags from the command line
+fn parse_args() -> Result<impl PluginGroup, ArgsError> {
+    Ok(GamePlugin::from_args(std::env::args())?
+        .set(ReplayManagementPlugin::from_args(std::env::args()))
+        .set(AutosplitterPlugin::from_args(std::env::args())?))
+}
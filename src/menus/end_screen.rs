@@ -1,8 +1,12 @@
 use super::{
-    level_select::BackButtonMarker, main_menu::BackgroundMenuTileMarker, CycleCount, DeathCount,
-    MenuCameraMarker, SpeedrunTimer, UI_RENDER_LAYER,
+    format_time, level_select::BackButtonMarker, main_menu::BackgroundMenuTileMarker, CycleCount,
+    DeathCount, MenuCameraMarker, SpeedrunTimer, UI_RENDER_LAYER,
+};
+use crate::{
+    level::{BestTimes, RunSplits},
+    splits::ExportedSplits,
+    state::LevelLoadingState,
 };
-use crate::state::LevelLoadingState;
 use bevy::prelude::*;
 
 #[derive(Component)]
@@ -13,6 +17,9 @@ pub fn create_end_screen_menu(
     speedrun_timer: Res<SpeedrunTimer>,
     death_count: Res<DeathCount>,
     cycle_count: Res<CycleCount>,
+    run_splits: Res<RunSplits>,
+    best_times: Res<BestTimes>,
+    exported_splits: Res<ExportedSplits>,
     asset_server: Res<AssetServer>,
 ) {
     let mut camera = Camera2dBundle::default();
@@ -58,7 +65,7 @@ pub fn create_end_screen_menu(
                 .spawn(ButtonBundle {
                     style: Style {
                         width: Val::Percent(90.0),
-                        height: Val::Percent(35.0),
+                        height: Val::Percent(25.0),
                         border: UiRect::all(Val::Px(5.0)),
                         // horizontally center child text
                         justify_content: JustifyContent::Center,
@@ -71,9 +78,8 @@ pub fn create_end_screen_menu(
                 .with_children(|parent| {
                     parent.spawn(TextBundle::from_section(
                         format!(
-                            "Congratulations! You finished the game in {}m {:.2}s with a total of {} deaths and {} cycles.",
-                            (speedrun_timer.0.elapsed_secs() / 60.) as isize,
-                            speedrun_timer.0.elapsed_secs() % 60.,
+                            "Congratulations! You finished the game in {} with a total of {} deaths and {} cycles.",
+                            format_time(speedrun_timer.0.elapsed_secs()),
                             death_count.0,
                             cycle_count.0,
                         ),
@@ -85,11 +91,52 @@ pub fn create_end_screen_menu(
                         },
                     )).insert(UI_RENDER_LAYER);
                 }).insert(UI_RENDER_LAYER);
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Percent(90.0),
+                        height: Val::Percent(40.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    let mut splits = run_splits
+                        .0
+                        .iter()
+                        .map(|split| {
+                            let best_time = best_times
+                                .get(&split.pack_id, &split.level_iid)
+                                .unwrap_or(split.level_time);
+                            format!(
+                                "{}: {} (best {})",
+                                split.level_name,
+                                format_time(split.level_time),
+                                format_time(best_time),
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    if let Some(path) = &exported_splits.0 {
+                        splits.push_str(&format!("\n\nSplits saved to {}", path.display()));
+                    }
+                    parent.spawn(TextBundle::from_section(
+                        splits,
+                        TextStyle {
+                            font: monocraft.clone(),
+                            font_size: 24.0,
+                            color: Color::srgb(0.9, 0.9, 0.9),
+                            ..default()
+                        },
+                    )).insert(UI_RENDER_LAYER);
+                }).insert(UI_RENDER_LAYER);
             parent
                 .spawn(ButtonBundle {
                     style: Style {
                         width: Val::Percent(90.0),
-                        height: Val::Percent(25.0),
+                        height: Val::Percent(20.0),
                         border: UiRect::all(Val::Px(5.0)),
                         // horizontally center child text
                         justify_content: JustifyContent::Center,
@@ -1,7 +1,10 @@
-use super::{main_menu::BackgroundMenuTileMarker, MenuCameraMarker, UI_RENDER_LAYER};
+use super::{
+    format_time, main_menu::BackgroundMenuTileMarker, MenuCameraMarker, SpeedrunTimer,
+    UI_RENDER_LAYER,
+};
 use crate::{
-    level::{FromLevelSelect, LastAccessibleLevel, LEVEL_IIDS},
-    state::{LevelLoadingState, TargetLevel},
+    level::{manifest::LevelManifest, BestTimes, FromLevelSelect, LastAccessibleLevel, RunSplits},
+    state::{LevelLoadingState, TargetLevel, TargetPack},
 };
 use bevy::prelude::*;
 
@@ -9,7 +12,10 @@ use bevy::prelude::*;
 pub struct LevelSelectMenuNode;
 
 #[derive(Component)]
-pub struct LevelButtonMarker(usize);
+pub struct LevelButtonMarker {
+    pack: usize,
+    level: usize,
+}
 
 #[derive(Component)]
 pub struct BackButtonMarker;
@@ -17,6 +23,8 @@ pub struct BackButtonMarker;
 pub fn create_level_select_menu(
     mut commands: Commands,
     last_accessible_level: Res<LastAccessibleLevel>,
+    level_manifest: Res<LevelManifest>,
+    best_times: Res<BestTimes>,
     asset_server: Res<AssetServer>,
 ) {
     let mut camera = Camera2dBundle::default();
@@ -58,42 +66,76 @@ pub fn create_level_select_menu(
         })
         .insert(LevelSelectMenuNode)
         .with_children(|parent| {
-            for i in 0..LEVEL_IIDS.len() {
+            for (pack_index, pack) in level_manifest.packs.iter().enumerate() {
                 parent
-                    .spawn(ButtonBundle {
+                    .spawn(TextBundle::from_section(
+                        pack.name.clone(),
+                        TextStyle {
+                            font: monocraft.clone(),
+                            font_size: 40.0,
+                            color: Color::srgb(0.9, 0.9, 0.9),
+                            ..default()
+                        },
+                    ))
+                    .insert(UI_RENDER_LAYER);
+                parent
+                    .spawn(NodeBundle {
                         style: Style {
                             width: Val::Percent(100.0),
-                            height: Val::Percent(10.0),
-                            border: UiRect::all(Val::Px(5.0)),
-                            // horizontally center child text
                             justify_content: JustifyContent::Center,
-                            // vertically center child text
-                            align_items: AlignItems::Center,
+                            flex_direction: FlexDirection::Row,
+                            flex_wrap: FlexWrap::Wrap,
                             ..default()
                         },
                         ..default()
                     })
-                    .insert(LevelButtonMarker(i))
                     .with_children(|parent| {
-                        parent
-                            .spawn(TextBundle::from_section(
-                                format!(
-                                    "Level {} {}",
-                                    i + 1,
-                                    if i > last_accessible_level.0 {
-                                        "[LOCKED]"
-                                    } else {
-                                        ""
-                                    }
-                                ),
-                                TextStyle {
-                                    font: monocraft.clone(),
-                                    font_size: 40.0,
-                                    color: Color::srgb(0.9, 0.9, 0.9),
+                        for (i, level) in pack.levels.iter().enumerate() {
+                            parent
+                                .spawn(ButtonBundle {
+                                    style: Style {
+                                        width: Val::Percent(30.0),
+                                        height: Val::Px(60.0),
+                                        border: UiRect::all(Val::Px(5.0)),
+                                        // horizontally center child text
+                                        justify_content: JustifyContent::Center,
+                                        // vertically center child text
+                                        align_items: AlignItems::Center,
+                                        ..default()
+                                    },
                                     ..default()
-                                },
-                            ))
-                            .insert(UI_RENDER_LAYER);
+                                })
+                                .insert(LevelButtonMarker {
+                                    pack: pack_index,
+                                    level: i,
+                                })
+                                .with_children(|parent| {
+                                    parent
+                                        .spawn(TextBundle::from_section(
+                                            format!(
+                                                "{} {}",
+                                                level.name,
+                                                if i > last_accessible_level.get(&pack.id) {
+                                                    "[LOCKED]".to_string()
+                                                } else if let Some(best_time) =
+                                                    best_times.get(&pack.id, &level.iid)
+                                                {
+                                                    format_time(best_time)
+                                                } else {
+                                                    "".to_string()
+                                                }
+                                            ),
+                                            TextStyle {
+                                                font: monocraft.clone(),
+                                                font_size: 30.0,
+                                                color: Color::srgb(0.9, 0.9, 0.9),
+                                                ..default()
+                                            },
+                                        ))
+                                        .insert(UI_RENDER_LAYER);
+                                })
+                                .insert(UI_RENDER_LAYER);
+                        }
                     })
                     .insert(UI_RENDER_LAYER);
             }
@@ -134,17 +176,28 @@ pub fn handle_level_select_menu_clicks(
     back_button_query: Query<&Interaction, (Changed<Interaction>, With<BackButtonMarker>)>,
     mut next_state: ResMut<NextState<LevelLoadingState>>,
     mut from_level_select: ResMut<FromLevelSelect>,
+    mut target_pack: ResMut<TargetPack>,
     mut target_level: ResMut<TargetLevel>,
+    mut speedrun_timer: ResMut<SpeedrunTimer>,
+    mut run_splits: ResMut<RunSplits>,
     last_accessible_level: Res<LastAccessibleLevel>,
+    level_manifest: Res<LevelManifest>,
 ) {
     for (interaction, level_button_marker) in level_select_query.iter() {
         if *interaction != Interaction::Pressed {
             return;
         }
-        if level_button_marker.0 > last_accessible_level.0 {
+        let Some(pack) = level_manifest.packs.get(level_button_marker.pack) else {
+            return;
+        };
+        if level_button_marker.level > last_accessible_level.get(&pack.id) {
             return;
         }
-        target_level.0 = level_button_marker.0;
+        target_pack.0 = level_button_marker.pack;
+        target_level.0 = level_button_marker.level;
+        // a new run, so its splits and time don't carry on from the last one
+        speedrun_timer.0.reset();
+        run_splits.0.clear();
         next_state.set(LevelLoadingState::Loading);
         from_level_select.0 = true;
     }
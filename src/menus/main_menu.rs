@@ -1,5 +1,14 @@
-use super::{CycleCount, DeathCount, MenuCameraMarker, SpeedrunTimer, UI_RENDER_LAYER};
-use crate::state::{LevelLoadingState, TargetLevel};
+use super::{
+    format_time, CycleCount, DeathCount, MenuCameraMarker, SpeedrunTimer, UI_RENDER_LAYER,
+};
+use crate::{
+    level::{manifest::LevelManifest, RunSplits},
+    save::{
+        delete_save_file, write_save_file, ActiveSaveSlot, SaveData, SaveDirectory, SaveSlot,
+        SaveSlots, SAVE_SLOT_COUNT,
+    },
+    state::{LevelLoadingState, TargetLevel, TargetPack},
+};
 use bevy::prelude::*;
 
 #[derive(Component)]
@@ -14,6 +23,18 @@ pub struct LevelSelectButtonMarker;
 #[derive(Component)]
 pub struct BackgroundMenuTileMarker;
 
+#[derive(Component)]
+pub struct SaveSlotButtonMarker(usize);
+
+#[derive(Component)]
+pub struct SaveSlotTextMarker(usize);
+
+#[derive(Component)]
+pub struct CopySaveSlotButtonMarker(usize);
+
+#[derive(Component)]
+pub struct DeleteSaveSlotButtonMarker(usize);
+
 pub fn create_main_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
     let mut camera = Camera2dBundle::default();
     camera.camera.order = 11;
@@ -84,6 +105,23 @@ pub fn create_main_menu(mut commands: Commands, asset_server: Res<AssetServer>)
                         .insert(UI_RENDER_LAYER);
                 })
                 .insert(UI_RENDER_LAYER);
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(25.0),
+                        justify_content: JustifyContent::Center,
+                        flex_direction: FlexDirection::Row,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for slot in 0..SAVE_SLOT_COUNT {
+                        spawn_save_slot(parent, slot, &monocraft);
+                    }
+                })
+                .insert(UI_RENDER_LAYER);
             parent
                 .spawn(ButtonBundle {
                     style: Style {
@@ -145,14 +183,212 @@ pub fn create_main_menu(mut commands: Commands, asset_server: Res<AssetServer>)
         });
 }
 
+fn spawn_save_slot(parent: &mut ChildBuilder, slot: usize, monocraft: &Handle<Font>) {
+    let text_style = TextStyle {
+        font: monocraft.clone(),
+        font_size: 20.0,
+        color: Color::srgb(0.9, 0.9, 0.9),
+        ..default()
+    };
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(30.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn(ButtonBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(75.0),
+                        border: UiRect::all(Val::Px(5.0)),
+                        // horizontally center child text
+                        justify_content: JustifyContent::Center,
+                        // vertically center child text
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .insert(SaveSlotButtonMarker(slot))
+                .with_children(|parent| {
+                    parent
+                        .spawn(TextBundle::from_section("", text_style.clone()))
+                        .insert(SaveSlotTextMarker(slot))
+                        .insert(UI_RENDER_LAYER);
+                })
+                .insert(UI_RENDER_LAYER);
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(25.0),
+                        flex_direction: FlexDirection::Row,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for (label, is_copy) in [("Copy", true), ("Delete", false)] {
+                        let mut button = parent.spawn(ButtonBundle {
+                            style: Style {
+                                width: Val::Percent(50.0),
+                                height: Val::Percent(100.0),
+                                // horizontally center child text
+                                justify_content: JustifyContent::Center,
+                                // vertically center child text
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            ..default()
+                        });
+                        if is_copy {
+                            button.insert(CopySaveSlotButtonMarker(slot));
+                        } else {
+                            button.insert(DeleteSaveSlotButtonMarker(slot));
+                        }
+                        button
+                            .with_children(|parent| {
+                                parent
+                                    .spawn(TextBundle::from_section(label, text_style.clone()))
+                                    .insert(UI_RENDER_LAYER);
+                            })
+                            .insert(UI_RENDER_LAYER);
+                    }
+                })
+                .insert(UI_RENDER_LAYER);
+        })
+        .insert(UI_RENDER_LAYER);
+}
+
+/// Describes a save slot's furthest level in the first level pack, run time and deaths
+fn save_slot_summary(slot: usize, save_slot: &SaveSlot, level_manifest: &LevelManifest) -> String {
+    let save_data = match save_slot {
+        SaveSlot::Empty => return format!("Slot {}\nEmpty", slot + 1),
+        SaveSlot::Unreadable(_) => return format!("Slot {}\nUnreadable", slot + 1),
+        SaveSlot::Saved(save_data) => save_data,
+    };
+    let furthest_level = level_manifest
+        .packs
+        .first()
+        .map(|pack| {
+            let last_accessible_level = save_data
+                .last_accessible_level
+                .get(&pack.id)
+                .copied()
+                .unwrap_or(0);
+            pack.levels
+                .get(last_accessible_level)
+                .map_or_else(|| "Complete".to_string(), |level| level.name.clone())
+        })
+        .unwrap_or_default();
+    format!(
+        "Slot {}\n{}\n{}\n{} deaths",
+        slot + 1,
+        furthest_level,
+        format_time(save_data.run.speedrun_time),
+        save_data.run.death_count,
+    )
+}
+
+pub fn update_save_slots(
+    mut query_slot_text: Query<(&mut Text, &SaveSlotTextMarker)>,
+    mut query_slot_button: Query<(&mut BorderColor, &SaveSlotButtonMarker)>,
+    save_slots: Res<SaveSlots>,
+    active_save_slot: Res<ActiveSaveSlot>,
+    level_manifest: Res<LevelManifest>,
+) {
+    for (mut text, SaveSlotTextMarker(slot)) in query_slot_text.iter_mut() {
+        let summary = save_slot_summary(*slot, &save_slots.0[*slot], &level_manifest);
+        // only touch the text when it changes so it isn't laid out again every frame
+        if text.sections[0].value != summary {
+            text.sections[0].value = summary;
+        }
+    }
+    for (mut border_color, SaveSlotButtonMarker(slot)) in query_slot_button.iter_mut() {
+        let color = if *slot == active_save_slot.0 {
+            Color::srgb(0.9, 0.9, 0.9)
+        } else {
+            Color::NONE
+        };
+        if border_color.0 != color {
+            border_color.0 = color;
+        }
+    }
+}
+
+pub fn handle_save_slot_clicks(
+    mut commands: Commands,
+    slot_query: Query<(&Interaction, &SaveSlotButtonMarker), Changed<Interaction>>,
+    copy_query: Query<(&Interaction, &CopySaveSlotButtonMarker), Changed<Interaction>>,
+    delete_query: Query<(&Interaction, &DeleteSaveSlotButtonMarker), Changed<Interaction>>,
+    save_directory: Res<SaveDirectory>,
+    mut active_save_slot: ResMut<ActiveSaveSlot>,
+    mut save_slots: ResMut<SaveSlots>,
+) {
+    for (interaction, SaveSlotButtonMarker(slot)) in slot_query.iter() {
+        if *interaction != Interaction::Pressed || *slot == active_save_slot.0 {
+            continue;
+        }
+        active_save_slot.0 = *slot;
+        let save_data = save_slots.0[*slot].save_data().cloned().unwrap_or_default();
+        commands.add(move |world: &mut World| save_data.apply(world));
+    }
+    for (interaction, CopySaveSlotButtonMarker(slot)) in copy_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        // copies go to the first empty slot, so nothing is ever overwritten
+        let Some(save_data) = save_slots.0[*slot].save_data().cloned() else {
+            continue;
+        };
+        let Some(empty_slot) = save_slots
+            .0
+            .iter()
+            .position(|save_slot| *save_slot == SaveSlot::Empty)
+        else {
+            continue;
+        };
+        if let Some(directory) = &save_directory.0 {
+            if let Err(e) = write_save_file(directory, empty_slot, &save_data) {
+                warn!("could not copy save slot {}: {e}", slot + 1);
+                continue;
+            }
+        }
+        save_slots.0[empty_slot] = SaveSlot::Saved(save_data);
+    }
+    for (interaction, DeleteSaveSlotButtonMarker(slot)) in delete_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        if let Some(directory) = &save_directory.0 {
+            if let Err(e) = delete_save_file(directory, *slot) {
+                warn!("could not delete save slot {}: {e}", slot + 1);
+                continue;
+            }
+        }
+        save_slots.0[*slot] = SaveSlot::Empty;
+        if *slot == active_save_slot.0 {
+            commands.add(|world: &mut World| SaveData::default().apply(world));
+        }
+    }
+}
+
 pub fn handle_main_menu_clicks(
     start_game_query: Query<&Interaction, (Changed<Interaction>, With<StartGameButtonMarker>)>,
     level_select_query: Query<&Interaction, (Changed<Interaction>, With<LevelSelectButtonMarker>)>,
     mut next_state: ResMut<NextState<LevelLoadingState>>,
     mut speedrun_timer: ResMut<SpeedrunTimer>,
+    mut target_pack: ResMut<TargetPack>,
     mut target_level: ResMut<TargetLevel>,
     mut death_counter: ResMut<DeathCount>,
     mut cycle_counter: ResMut<CycleCount>,
+    mut run_splits: ResMut<RunSplits>,
 ) {
     for interaction in start_game_query.iter() {
         if *interaction != Interaction::Pressed {
@@ -161,6 +397,8 @@ pub fn handle_main_menu_clicks(
         speedrun_timer.0.reset();
         death_counter.0 = 0;
         cycle_counter.0 = 0;
+        run_splits.0.clear();
+        target_pack.0 = 0;
         target_level.0 = 0;
         next_state.set(LevelLoadingState::Loading);
     }
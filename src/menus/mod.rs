@@ -3,9 +3,12 @@ use end_screen::{cleanup_end_screen, create_end_screen_menu, handle_end_screen_c
 use level_select::{
     cleanup_level_select_menu, create_level_select_menu, handle_level_select_menu_clicks,
 };
-use main_menu::{cleanup_main_menu, create_main_menu, handle_main_menu_clicks};
+use main_menu::{
+    cleanup_main_menu, create_main_menu, handle_main_menu_clicks, handle_save_slot_clicks,
+    update_save_slots,
+};
 
-use crate::state::LevelLoadingState;
+use crate::{splits::export_splits, state::LevelLoadingState};
 
 pub struct MenuManagementPlugin;
 
@@ -17,14 +20,16 @@ const UI_RENDER_LAYER: RenderLayers = RenderLayers::layer(11);
 
 impl Plugin for MenuManagementPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(SpeedrunTimer(Stopwatch::new()))
-            .insert_resource(DeathCount(0))
-            .insert_resource(CycleCount(0))
-            .add_systems(OnEnter(LevelLoadingState::MainMenu), create_main_menu)
+        app.add_systems(OnEnter(LevelLoadingState::MainMenu), create_main_menu)
             .add_systems(OnExit(LevelLoadingState::MainMenu), cleanup_main_menu)
             .add_systems(
                 Update,
-                (handle_main_menu_clicks).run_if(in_state(LevelLoadingState::MainMenu)),
+                (
+                    handle_main_menu_clicks,
+                    handle_save_slot_clicks,
+                    update_save_slots,
+                )
+                    .run_if(in_state(LevelLoadingState::MainMenu)),
             )
             .add_systems(
                 OnEnter(LevelLoadingState::LevelSelect),
@@ -40,20 +45,20 @@ impl Plugin for MenuManagementPlugin {
             )
             .add_systems(
                 OnEnter(LevelLoadingState::EndScreen),
-                create_end_screen_menu,
+                create_end_screen_menu.after(export_splits),
             )
             .add_systems(OnExit(LevelLoadingState::EndScreen), cleanup_end_screen)
             .add_systems(
                 Update,
                 (handle_end_screen_clicks).run_if(in_state(LevelLoadingState::EndScreen)),
-            )
-            .add_systems(Update, tick_speedrun_timer);
+            );
     }
 }
 
 #[derive(Component)]
 struct MenuCameraMarker;
 
+/// In-game time of the current run, only ticked while a level is being played
 #[derive(Resource)]
 pub struct SpeedrunTimer(pub Stopwatch);
 
@@ -63,6 +68,7 @@ pub struct DeathCount(pub usize);
 #[derive(Resource)]
 pub struct CycleCount(pub usize);
 
-fn tick_speedrun_timer(time: Res<Time>, mut speedrun_timer: ResMut<SpeedrunTimer>) {
-    speedrun_timer.0.tick(time.delta());
+/// Formats a time in seconds the way the menus show it, e.g. `2m 5.31s`
+pub fn format_time(seconds: f32) -> String {
+    format!("{}m {:.2}s", (seconds / 60.) as isize, seconds % 60.)
 }
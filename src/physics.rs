@@ -0,0 +1,118 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+/// Rate gameplay and physics are updated at, independent of the frame rate
+pub const FIXED_TIMESTEP_HZ: f64 = 60.;
+
+pub const PIXELS_PER_METER: f32 = 24.;
+
+/// Interpolated movement longer than this is a teleport, like looping around the level or
+/// respawning, and is shown immediately instead
+pub const MAX_INTERPOLATION_DISTANCE: f32 = 64.;
+
+/// Steps Rapier in `FixedUpdate` and smooths the movement of fixed-rate bodies between ticks
+#[derive(Default)]
+pub struct PhysicsManagementPlugin {
+    /// Draws the outline of every collider, which needs a window
+    pub debug_render: bool,
+}
+
+impl Plugin for PhysicsManagementPlugin {
+    fn build(&self, app: &mut App) {
+        // inserted before the Rapier plugin so it doesn't add its variable timestep default
+        app.insert_resource(Time::<Fixed>::from_hz(FIXED_TIMESTEP_HZ))
+            .insert_resource(RapierConfiguration {
+                timestep_mode: TimestepMode::Fixed {
+                    dt: (1. / FIXED_TIMESTEP_HZ) as f32,
+                    substeps: 1,
+                },
+                ..RapierConfiguration::new(PIXELS_PER_METER)
+            })
+            .add_plugins(
+                RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(PIXELS_PER_METER)
+                    .in_fixed_schedule(),
+            )
+            .add_systems(FixedFirst, restore_physics_translation)
+            .add_systems(
+                FixedUpdate,
+                store_physics_translation.after(PhysicsSet::Writeback),
+            )
+            .add_systems(Update, interpolate_translation);
+
+        if self.debug_render {
+            app.add_plugins(RapierDebugRenderPlugin::default());
+        }
+    }
+}
+
+/// Draws an entity moved in `FixedUpdate` between its last two physics positions, so it moves
+/// smoothly at frame rates that aren't a multiple of the fixed rate
+#[derive(Component)]
+pub struct TranslationInterpolation {
+    previous: Vec3,
+    current: Vec3,
+    /// Translation written for rendering, which has to be swapped back for the physics one before
+    /// the next tick
+    rendered: Option<Vec3>,
+}
+
+impl TranslationInterpolation {
+    pub fn new(translation: Vec3) -> Self {
+        Self {
+            previous: translation,
+            current: translation,
+            rendered: None,
+        }
+    }
+
+    /// Treats `translation` as a teleport that shouldn't be interpolated
+    fn snap(&mut self, translation: Vec3) {
+        self.previous = translation;
+        self.current = translation;
+    }
+}
+
+fn restore_physics_translation(mut query: Query<(&mut Transform, &mut TranslationInterpolation)>) {
+    for (mut transform, mut interpolation) in query.iter_mut() {
+        let Some(rendered) = interpolation.rendered.take() else {
+            continue;
+        };
+        if transform.translation == rendered {
+            transform.translation = interpolation.current;
+        } else {
+            // moved by a system outside the fixed schedule since it was rendered
+            interpolation.snap(transform.translation);
+        }
+    }
+}
+
+fn store_physics_translation(mut query: Query<(&Transform, &mut TranslationInterpolation)>) {
+    for (transform, mut interpolation) in query.iter_mut() {
+        interpolation.previous = interpolation.current;
+        interpolation.current = transform.translation;
+    }
+}
+
+/// Systems in `Update` that follow an interpolated entity, like cameras, should run after this
+pub fn interpolate_translation(
+    fixed_time: Res<Time<Fixed>>,
+    mut query: Query<(&mut Transform, &mut TranslationInterpolation)>,
+) {
+    for (mut transform, mut interpolation) in query.iter_mut() {
+        let expected = interpolation.rendered.unwrap_or(interpolation.current);
+        if transform.translation != expected {
+            interpolation.snap(transform.translation);
+        }
+        let translation = if interpolation.previous.distance(interpolation.current)
+            > MAX_INTERPOLATION_DISTANCE
+        {
+            interpolation.current
+        } else {
+            interpolation
+                .previous
+                .lerp(interpolation.current, fixed_time.overstep_fraction())
+        };
+        transform.translation = translation;
+        interpolation.rendered = Some(translation);
+    }
+}
@@ -0,0 +1,53 @@
+use bevy::prelude::*;
+
+use crate::player::{PlayerContacts, PlayerMarker};
+
+use super::{Ability, AbilitySet, JumpPriority, PlayerJump, RegisterAbility};
+
+pub struct AirJumpTokenPlugin;
+
+impl Plugin for AirJumpTokenPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_ability::<AirJumpTokens>().add_systems(
+            FixedUpdate,
+            (
+                drop_air_jump_tokens.in_set(AbilitySet::Contacts),
+                air_jump.in_set(JumpPriority::AirJumpToken),
+            ),
+        );
+    }
+}
+
+/// Jumps from jump tokens, which are only kept until the player touches the ground or a wall
+#[derive(Component, Debug, Default)]
+pub struct AirJumpTokens(pub usize);
+
+impl Ability for AirJumpTokens {
+    const HUD_ICON: &'static str = "jump_token_icon.png";
+    const HUD_ORDER: u8 = 4;
+
+    fn hud_icon_count(&self) -> usize {
+        self.0
+    }
+}
+
+fn drop_air_jump_tokens(
+    mut query_player: Query<(&mut AirJumpTokens, &PlayerContacts), With<PlayerMarker>>,
+) {
+    let Ok((mut air_jump_tokens, contacts)) = query_player.get_single_mut() else {
+        return;
+    };
+    if contacts.grounded || contacts.on_wall.contains(&true) {
+        air_jump_tokens.0 = 0;
+    }
+}
+
+fn air_jump(mut query_player: Query<(&mut AirJumpTokens, &mut PlayerJump)>) {
+    let Ok((mut air_jump_tokens, mut player_jump)) = query_player.get_single_mut() else {
+        return;
+    };
+    if *player_jump == PlayerJump::Wanted && air_jump_tokens.0 >= 1 {
+        air_jump_tokens.0 -= 1;
+        *player_jump = PlayerJump::Jump;
+    }
+}
@@ -0,0 +1,179 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    input::GameplayInput,
+    level::fade::PlayerRespawnedEvent,
+    player::{PlayerContacts, PlayerMarker, PlayerState, PlayerStatus},
+};
+
+use super::{Ability, AbilitySet, RegisterAbility};
+
+pub const DASH_KEYCODE: KeyCode = KeyCode::KeyC;
+
+/// LDtk level field making the player invincible to hazards while dashing, a bool. Levels without
+/// it have no i-frames.
+const DASH_IFRAMES_FIELD: &str = "dash_iframes";
+
+const DASH_SPEED: f32 = 250.;
+const DASH_DURATION: Duration = Duration::from_millis(150);
+/// Time from the start of one dash until the player can dash again
+const DASH_COOLDOWN: Duration = Duration::from_millis(400);
+
+pub struct DashPlugin;
+
+impl Plugin for DashPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DashIframes>()
+            .add_event::<PlayerRespawnedEvent>()
+            .register_ability::<Dash>()
+            .add_systems(Update, (read_dash_iframes, end_dash_on_respawn))
+            .add_systems(
+                FixedUpdate,
+                (
+                    refill_dash.in_set(AbilitySet::Contacts),
+                    dash.in_set(AbilitySet::Movement),
+                ),
+            );
+    }
+}
+
+/// A quick straight dash the way the player is facing, on the ground or in the air. It's used up
+/// until the player touches the ground or a wall, like double jumps.
+#[derive(Component, Debug)]
+pub struct Dash {
+    /// Whether the player can dash before touching the ground or a wall again
+    pub ready: bool,
+    dashing: bool,
+    /// -1 for left and 1 for right
+    direction: f32,
+    duration: Timer,
+    cooldown: Timer,
+}
+
+impl Default for Dash {
+    fn default() -> Self {
+        let mut cooldown = Timer::new(DASH_COOLDOWN, TimerMode::Once);
+        cooldown.tick(DASH_COOLDOWN);
+        Self {
+            ready: true,
+            dashing: false,
+            direction: 1.,
+            duration: Timer::new(DASH_DURATION, TimerMode::Once),
+            cooldown,
+        }
+    }
+}
+
+impl Dash {
+    pub fn dashing(&self) -> bool {
+        self.dashing
+    }
+}
+
+impl Ability for Dash {
+    const HUD_ICON: &'static str = "dash_icon.png";
+    const HUD_ORDER: u8 = 1;
+}
+
+/// Whether the current level gives the player i-frames while dashing, read from its
+/// [`DASH_IFRAMES_FIELD`]
+#[derive(Resource, Default)]
+pub struct DashIframes(pub bool);
+
+fn read_dash_iframes(
+    query_level: Query<&LevelIid, Added<LevelIid>>,
+    query_ldtk_world: Query<&Handle<LdtkProject>>,
+    ldtk_projects: Res<Assets<LdtkProject>>,
+    mut dash_iframes: ResMut<DashIframes>,
+) {
+    let Ok(level_iid) = query_level.get_single() else {
+        return;
+    };
+    dash_iframes.0 = query_ldtk_world
+        .get_single()
+        .ok()
+        .and_then(|handle| ldtk_projects.get(handle))
+        .and_then(|project| project.get_raw_level_by_iid(level_iid.get()))
+        .and_then(|level| level.get_bool_field(DASH_IFRAMES_FIELD).ok())
+        .is_some_and(|iframes| *iframes);
+}
+
+fn refill_dash(
+    time: Res<Time>,
+    mut query_player: Query<(&mut Dash, &PlayerContacts), With<PlayerMarker>>,
+) {
+    let Ok((mut dash, contacts)) = query_player.get_single_mut() else {
+        return;
+    };
+    dash.cooldown.tick(time.delta());
+    if contacts.grounded || contacts.on_wall.contains(&true) {
+        dash.ready = true;
+    }
+}
+
+fn dash(
+    time: Res<Time>,
+    keys: Res<GameplayInput>,
+    dash_iframes: Res<DashIframes>,
+    mut query_player: Query<
+        (
+            &mut Dash,
+            &mut Velocity,
+            &Sprite,
+            &mut PlayerState,
+            &mut PlayerStatus,
+        ),
+        With<PlayerMarker>,
+    >,
+) {
+    let Ok((mut dash, mut velocity, sprite, mut player_state, mut player_status)) =
+        query_player.get_single_mut()
+    else {
+        return;
+    };
+
+    if dash.dashing {
+        dash.duration.tick(time.delta());
+        // jumping or grabbing a wall cuts the dash short
+        if *player_state != PlayerState::Dashing {
+            dash.dashing = false;
+        } else if dash.duration.finished() {
+            dash.dashing = false;
+            *player_state = PlayerState::Falling;
+        }
+    } else if keys.just_pressed(DASH_KEYCODE) && dash.ready && dash.cooldown.finished() {
+        dash.ready = false;
+        dash.dashing = true;
+        dash.direction = if sprite.flip_x { -1. } else { 1. };
+        dash.duration.reset();
+        dash.cooldown.reset();
+        *player_state = PlayerState::Dashing;
+    }
+
+    if dash.dashing {
+        // no gravity while dashing
+        velocity.linvel = Vec2::new(dash.direction * DASH_SPEED, 0.);
+    }
+    player_status.invincible = dash.dashing && dash_iframes.0;
+}
+
+fn end_dash_on_respawn(
+    mut respawned_events: EventReader<PlayerRespawnedEvent>,
+    mut query_player: Query<(&mut Dash, &mut PlayerState, &mut PlayerStatus), With<PlayerMarker>>,
+) {
+    if respawned_events.read().count() == 0 {
+        return;
+    }
+    let Ok((mut dash, mut player_state, mut player_status)) = query_player.get_single_mut() else {
+        return;
+    };
+    if dash.dashing {
+        dash.dashing = false;
+        *player_state = PlayerState::Falling;
+    }
+    player_status.invincible = false;
+}
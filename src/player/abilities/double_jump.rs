@@ -0,0 +1,61 @@
+use bevy::prelude::*;
+
+use crate::player::{PlayerContacts, PlayerMarker};
+
+use super::{Ability, AbilitySet, JumpPriority, PlayerJump, RegisterAbility};
+
+pub struct DoubleJumpPlugin;
+
+impl Plugin for DoubleJumpPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_ability::<DoubleJump>().add_systems(
+            FixedUpdate,
+            (
+                refill_double_jumps.in_set(AbilitySet::Contacts),
+                double_jump.in_set(JumpPriority::DoubleJump),
+            ),
+        );
+    }
+}
+
+/// Jumps in the air, refilled on the ground and by wall jumps
+#[derive(Component, Debug, Default)]
+pub struct DoubleJump {
+    /// One per double jump pickup
+    pub max: usize,
+    pub remaining: usize,
+}
+
+impl Ability for DoubleJump {
+    const HUD_ICON: &'static str = "double_jump_icon.png";
+    const HUD_ORDER: u8 = 2;
+
+    fn hud_icon_count(&self) -> usize {
+        self.max
+    }
+}
+
+fn refill_double_jumps(
+    mut query_player: Query<(&mut DoubleJump, &PlayerContacts), With<PlayerMarker>>,
+) {
+    let Ok((mut double_jump, contacts)) = query_player.get_single_mut() else {
+        return;
+    };
+    if contacts.grounded {
+        double_jump.remaining = double_jump.max;
+    }
+}
+
+fn double_jump(mut query_player: Query<(&mut DoubleJump, &mut PlayerJump)>) {
+    let Ok((mut double_jump, mut player_jump)) = query_player.get_single_mut() else {
+        return;
+    };
+    match *player_jump {
+        PlayerJump::WallJump => double_jump.remaining = double_jump.max,
+        PlayerJump::Wanted if double_jump.remaining >= 1 => {
+            double_jump.remaining -= 1;
+            *player_jump = PlayerJump::Jump;
+        }
+        _ => {}
+    }
+}
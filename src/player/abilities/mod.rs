@@ -0,0 +1,152 @@
+//! Movement abilities the player picks up in a level. Each is a component on the player with a
+//! plugin of its own, hooking into the player's tick through [`AbilitySet`]. Jumps the player can't
+//! make from the ground are handed to the abilities in [`JumpPriority`] order through
+//! [`PlayerJump`], so the first one able to grant it is the one used up.
+
+use bevy::prelude::*;
+
+use crate::state::LevelLoadingState;
+
+use super::{PlayerInventory, PlayerMarker};
+
+pub mod air_jump_token;
+pub mod dash;
+pub mod double_jump;
+pub mod wall_jump;
+
+use air_jump_token::AirJumpTokenPlugin;
+use dash::DashPlugin;
+use double_jump::DoubleJumpPlugin;
+use wall_jump::WallJumpPlugin;
+
+/// Where keys go in the HUD, between the abilities and the jump tokens
+const KEY_HUD_ORDER: u8 = 3;
+
+/// Adds every ability
+pub struct AbilitiesPlugin;
+
+impl Plugin for AbilitiesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HudItems>()
+            .configure_sets(
+                FixedUpdate,
+                (
+                    JumpPriority::WallJump,
+                    JumpPriority::DoubleJump,
+                    JumpPriority::AirJumpToken,
+                )
+                    .chain()
+                    .in_set(AbilitySet::Jump),
+            )
+            .add_systems(
+                Update,
+                (
+                    clear_hud_items.before(HudItemSet),
+                    list_keys.in_set(HudItemSet),
+                )
+                    .run_if(in_state(LevelLoadingState::Loaded)),
+            )
+            .add_plugins((
+                WallJumpPlugin,
+                DashPlugin,
+                DoubleJumpPlugin,
+                AirJumpTokenPlugin,
+            ));
+    }
+}
+
+/// A movement ability, shown in the HUD while the player has it
+pub trait Ability: Component {
+    /// Icon in the HUD, relative to the assets folder
+    const HUD_ICON: &'static str;
+    /// Where its icons go in the HUD, lowest first
+    const HUD_ORDER: u8;
+
+    /// How many icons it takes up in the HUD, like one per jump it grants
+    fn hud_icon_count(&self) -> usize {
+        1
+    }
+}
+
+pub trait RegisterAbility {
+    /// Lists the ability in the HUD while the player has it
+    fn register_ability<A: Ability>(&mut self) -> &mut Self;
+}
+
+impl RegisterAbility for App {
+    fn register_ability<A: Ability>(&mut self) -> &mut Self {
+        self.add_systems(
+            Update,
+            list_ability::<A>
+                .in_set(HudItemSet)
+                .run_if(in_state(LevelLoadingState::Loaded)),
+        )
+    }
+}
+
+/// Ability systems in `FixedUpdate`, by where they run in the player's tick
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AbilitySet {
+    /// Once it's known what the player is touching, before that updates their state
+    Contacts,
+    /// After the player decided to jump, before the jump is made. See [`JumpPriority`].
+    Jump,
+    /// After the player moved, while they're in control
+    Movement,
+}
+
+/// Abilities granting jumps in the air, in the order they're used up
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum JumpPriority {
+    WallJump,
+    DoubleJump,
+    AirJumpToken,
+}
+
+/// The jump the player makes this tick
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PlayerJump {
+    #[default]
+    None,
+    /// Jump pressed where the player can't jump by themselves, for an ability to grant
+    Wanted,
+    Jump,
+    /// Jump off a wall, which leaves the player facing away from it
+    WallJump,
+}
+
+/// Icons of everything the player carries, with where they go in the HUD
+#[derive(Resource, Default)]
+pub struct HudItems(pub Vec<(u8, &'static str)>);
+
+/// Systems in `Update` listing [`HudItems`]
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HudItemSet;
+
+fn clear_hud_items(mut hud_items: ResMut<HudItems>) {
+    hud_items.0.clear();
+}
+
+fn list_ability<A: Ability>(
+    mut hud_items: ResMut<HudItems>,
+    query_player: Query<&A, With<PlayerMarker>>,
+) {
+    let Ok(ability) = query_player.get_single() else {
+        return;
+    };
+    for _ in 0..ability.hud_icon_count() {
+        hud_items.0.push((A::HUD_ORDER, A::HUD_ICON));
+    }
+}
+
+fn list_keys(
+    mut hud_items: ResMut<HudItems>,
+    query_player: Query<&PlayerInventory, With<PlayerMarker>>,
+) {
+    let Ok(inventory) = query_player.get_single() else {
+        return;
+    };
+    for _ in 0..inventory.num_keys {
+        hud_items.0.push((KEY_HUD_ORDER, "key_icon.png"));
+    }
+}
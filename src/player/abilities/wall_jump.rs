@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::player::{PlayerContacts, PlayerMarker, PlayerState};
+
+use super::{Ability, AbilitySet, JumpPriority, PlayerJump, RegisterAbility};
+
+/// Fastest the player slides down a wall
+pub const WALL_SLIDE_SPEED: f32 = 75.;
+/// Time until the player can jump off a wall on the same side again
+pub const WALL_JUMP_COOLDOWN: Duration = Duration::from_millis(800);
+
+pub struct WallJumpPlugin;
+
+impl Plugin for WallJumpPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_ability::<WallJump>().add_systems(
+            FixedUpdate,
+            (
+                start_wall_slide.in_set(AbilitySet::Contacts),
+                wall_jump.in_set(JumpPriority::WallJump),
+                limit_wall_slide_speed.in_set(AbilitySet::Movement),
+            ),
+        );
+    }
+}
+
+/// Lets the player slide down walls and jump off them, once off each side at a time
+#[derive(Component, Debug)]
+pub struct WallJump {
+    /// Time until the player can jump off the left and right walls again
+    cooldowns: [Timer; 2],
+}
+
+impl Default for WallJump {
+    fn default() -> Self {
+        Self {
+            cooldowns: [
+                Timer::new(WALL_JUMP_COOLDOWN, TimerMode::Once),
+                Timer::new(WALL_JUMP_COOLDOWN, TimerMode::Once),
+            ],
+        }
+    }
+}
+
+impl Ability for WallJump {
+    const HUD_ICON: &'static str = "wall_jump_icon.png";
+    const HUD_ORDER: u8 = 0;
+}
+
+fn start_wall_slide(
+    time: Res<Time>,
+    mut query_player: Query<(&mut WallJump, &PlayerContacts, &mut PlayerState), With<PlayerMarker>>,
+) {
+    let Ok((mut wall_jump, contacts, mut player_state)) = query_player.get_single_mut() else {
+        return;
+    };
+    for cooldown in wall_jump.cooldowns.iter_mut() {
+        if !cooldown.finished() {
+            cooldown.tick(time.delta());
+        }
+    }
+    if contacts.on_wall.contains(&true) {
+        *player_state = PlayerState::Sliding;
+    }
+}
+
+fn wall_jump(mut query_player: Query<(&mut WallJump, &PlayerContacts, &mut PlayerJump)>) {
+    let Ok((mut wall_jump, contacts, mut player_jump)) = query_player.get_single_mut() else {
+        return;
+    };
+    if *player_jump != PlayerJump::Wanted {
+        return;
+    }
+    for side in 0..2 {
+        if contacts.on_wall[side] && wall_jump.cooldowns[side].finished() {
+            wall_jump.cooldowns[side].reset();
+            *player_jump = PlayerJump::WallJump;
+            return;
+        }
+    }
+}
+
+fn limit_wall_slide_speed(
+    mut query_player: Query<(&PlayerContacts, &mut Velocity), (With<WallJump>, With<PlayerMarker>)>,
+) {
+    let Ok((contacts, mut velocity)) = query_player.get_single_mut() else {
+        return;
+    };
+    if contacts.on_wall.contains(&true) && velocity.linvel.y < -WALL_SLIDE_SPEED {
+        velocity.linvel.y = -WALL_SLIDE_SPEED;
+    }
+}
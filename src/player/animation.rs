@@ -15,12 +15,15 @@ pub struct AnimationInfo {
     sliding_end: usize,
     falling_to_idle_start: usize,
     falling_to_idle_end: usize,
+    dashing_start: usize,
+    dashing_end: usize,
 
     moving_durations: Vec<u64>,
     jumping_durations: Vec<u64>,
     falling_durations: Vec<u64>,
     falling_to_idle_durations: Vec<u64>,
     sliding_durations: Vec<u64>,
+    dashing_durations: Vec<u64>,
 }
 
 #[derive(Component, Deref, DerefMut)]
@@ -39,12 +42,15 @@ impl Default for AnimationInfo {
             falling_to_idle_end: 10,
             sliding_start: 14,
             sliding_end: 17,
+            dashing_start: 21,
+            dashing_end: 21,
 
             moving_durations: vec![100, 100, 100, 100],
             jumping_durations: vec![100, 100, 100],
             falling_durations: vec![100, 100, 100],
             falling_to_idle_durations: vec![50, 50, 50, 50, 50],
             sliding_durations: vec![50, 50, 50, 50],
+            dashing_durations: vec![50],
         }
     }
 }
@@ -234,6 +240,24 @@ pub fn animate_player(
 
                     timer.set_duration(Duration::from_millis(50));
                 }
+                PlayerState::Dashing => {
+                    if atlas.index < animation_info.dashing_start
+                        || atlas.index > animation_info.dashing_end
+                    {
+                        atlas.index = animation_info.dashing_start;
+                    } else {
+                        atlas.index = if atlas.index == animation_info.dashing_end {
+                            atlas.index
+                        } else {
+                            atlas.index + 1
+                        };
+                    }
+
+                    timer.set_duration(Duration::from_millis(
+                        animation_info.dashing_durations
+                            [atlas.index - animation_info.dashing_start],
+                    ));
+                }
             }
         }
     }
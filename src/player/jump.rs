@@ -0,0 +1,90 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::input::GameplayInput;
+
+use super::{PlayerContacts, PlayerMarker, PlayerStatus};
+
+/// Keys that jump, held for a higher jump
+pub const JUMP_KEYCODES: [KeyCode; 2] = [KeyCode::ArrowUp, KeyCode::KeyZ];
+
+/// How the player's jump moves them, from take off to landing. Gravity multipliers scale the
+/// physics gravity.
+#[derive(Resource, Debug, Clone)]
+pub struct JumpTuning {
+    /// Upward speed a jump starts with
+    pub velocity: f32,
+    /// What the upward speed is multiplied by when the jump key is let go on the way up
+    pub release_multiplier: f32,
+    /// Vertical speed under which a held jump is at its top
+    pub apex_speed: f32,
+    /// Gravity multiplier at the top of a held jump, below 1 to hang there for a moment
+    pub apex_gravity: f32,
+    /// Gravity multiplier while falling, above 1 to come down faster than the player went up
+    pub fall_gravity: f32,
+    /// Fastest the player falls
+    pub max_fall_speed: f32,
+}
+
+impl Default for JumpTuning {
+    fn default() -> Self {
+        Self {
+            velocity: 130.,
+            release_multiplier: 0.5,
+            apex_speed: 20.,
+            apex_gravity: 0.5,
+            fall_gravity: 1.4,
+            max_fall_speed: 300.,
+        }
+    }
+}
+
+impl JumpTuning {
+    /// Gravity multiplier for a player moving up at `vertical_speed`, depending on whether they're
+    /// still holding the jump they made
+    pub fn gravity_scale(&self, vertical_speed: f32, holding_jump: bool) -> f32 {
+        if holding_jump && vertical_speed.abs() < self.apex_speed {
+            self.apex_gravity
+        } else if vertical_speed < 0. {
+            self.fall_gravity
+        } else {
+            1.
+        }
+    }
+}
+
+/// Shapes the jump made this tick or earlier: cuts it short once the jump key is let go, and sets
+/// the gravity for the part of the jump the player is in
+pub fn apply_jump_curve(
+    keys: Res<GameplayInput>,
+    jump_tuning: Res<JumpTuning>,
+    mut query_player: Query<
+        (
+            &PlayerContacts,
+            &mut PlayerStatus,
+            &mut Velocity,
+            &mut GravityScale,
+        ),
+        With<PlayerMarker>,
+    >,
+) {
+    let Ok((player_contacts, mut player_status, mut velocity, mut gravity_scale)) =
+        query_player.get_single_mut()
+    else {
+        return;
+    };
+
+    if player_status.holding_jump {
+        if !keys.any_pressed(JUMP_KEYCODES) {
+            player_status.holding_jump = false;
+            if velocity.linvel.y > 0. {
+                velocity.linvel.y *= jump_tuning.release_multiplier;
+            }
+        } else if player_contacts.grounded && velocity.linvel.y <= 0. {
+            player_status.holding_jump = false;
+        }
+    }
+
+    velocity.linvel.y = velocity.linvel.y.max(-jump_tuning.max_fall_speed);
+    gravity_scale.0 = jump_tuning.gravity_scale(velocity.linvel.y, player_status.holding_jump);
+}
@@ -4,35 +4,77 @@ use bevy_ecs_tilemap::tiles::TileTextureIndex;
 use bevy_rapier2d::prelude::*;
 use std::time::Duration;
 
+pub mod abilities;
 pub mod animation;
+pub mod jump;
 
 use crate::camera::{CameraPanning, CameraPanningState, PlayerCameraMarker, PLAYER_RENDER_LAYER};
-use crate::level::{BackwardsBarrier, KillPlayerMarker, SpikeInfo, SpikeMarker};
+use crate::input::GameplayInput;
+use crate::level::{BackwardsBarrier, KillPlayerMarker, LevelWrap, SpikeInfo, SpikeMarker};
+use crate::menus::CycleCount;
+use crate::physics::TranslationInterpolation;
 use crate::sound_effects::{SoundEffectEvent, SoundEffectType};
 use crate::state::LevelLoadingState;
 
+use abilities::{AbilitiesPlugin, AbilitySet, PlayerJump};
 use animation::{animate_player, AnimationInfo, AnimationTimer};
+use jump::{apply_jump_curve, JumpTuning};
+
+/// Added to the player's horizontal speed every tick the player runs
+pub const RUN_ACCELERATION: f32 = 55.;
+/// What the player's horizontal speed is divided by every tick
+pub const RUN_DRAG: f32 = 1.6;
+/// Shortest time between two jumps
+pub const JUMP_COOLDOWN: Duration = Duration::from_millis(200);
+/// How long after walking off a ledge the player can still jump off the ground
+pub const COYOTE_TIME: Duration = Duration::from_millis(100);
 
 pub struct PlayerManagementPlugin;
 
 impl Plugin for PlayerManagementPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(AnimationInfo::default())
+            .init_resource::<JumpTuning>()
             .add_event::<SetCheckpointEvent>()
+            .add_event::<LevelCompletedEvent>()
+            .add_event::<SoundEffectEvent>()
             .register_ldtk_entity::<PlayerBundle>("Player")
+            .add_plugins(AbilitiesPlugin)
+            .configure_sets(
+                FixedUpdate,
+                (
+                    AbilitySet::Contacts
+                        .after(update_player_contacts)
+                        .before(update_player_grounded),
+                    AbilitySet::Jump.after(move_player).before(apply_jump),
+                    AbilitySet::Movement
+                        .after(apply_jump_curve)
+                        .before(loop_player)
+                        .run_if(player_in_control),
+                )
+                    .before(PhysicsSet::SyncBackend)
+                    .run_if(in_state(LevelLoadingState::Loaded)),
+            )
             .add_systems(
                 Update,
+                (add_colliders, animate_player, set_player_checkpoint)
+                    .run_if(in_state(LevelLoadingState::Loaded)),
+            )
+            .add_systems(
+                FixedUpdate,
                 (
-                    add_colliders,
+                    update_player_contacts,
                     update_player_grounded,
                     tick_buffer_frames,
                     move_player,
+                    apply_jump,
+                    apply_jump_curve,
                     loop_player,
-                    animate_player,
-                    set_player_checkpoint,
                     kill_player,
                     exit_level,
                 )
+                    .chain()
+                    .before(PhysicsSet::SyncBackend)
                     .run_if(in_state(LevelLoadingState::Loaded)),
             );
     }
@@ -57,9 +99,13 @@ pub struct PlayerStatus {
     jump_cooldown: Timer,
     coyote_frames: Timer,
     jump_buffer: Timer,
+    /// Whether the jump key has been held since the player's last jump
+    holding_jump: bool,
     pub level_finished: bool,
     pub dead: bool,
     pub exiting: bool,
+    /// Hazards don't kill the player, though restarting still does
+    pub invincible: bool,
     // air_jumps: usize,
     // max_air_jumps: usize,
 }
@@ -75,17 +121,21 @@ pub enum PlayerState {
     FallingToIdle,
     Sliding,
     SlidingToJump,
+    Dashing,
 }
 
+/// What the player carries besides their abilities, see [`abilities`]
 #[derive(Component, Debug)]
 pub struct PlayerInventory {
     pub num_keys: usize,
-    pub max_extra_jumps: usize,
-    pub extra_jumps: usize,
-    pub air_jumps: usize,
-    pub wall_jump_cd: [Timer; 2],
+}
+
+/// What the player touched this tick
+#[derive(Component, Debug, Default)]
+pub struct PlayerContacts {
+    pub grounded: bool,
+    /// Whether the player is against a wall on their left and right
     pub on_wall: [bool; 2],
-    pub has_wall_jump: bool,
 }
 
 #[derive(Bundle, LdtkEntity)]
@@ -96,10 +146,13 @@ struct PlayerBundle {
     player_marker: PlayerMarker,
     player_status: PlayerStatus,
     player_inventory: PlayerInventory,
+    player_contacts: PlayerContacts,
+    player_jump: PlayerJump,
     rigid_body: RigidBody,
     collider: Collider,
     mass: AdditionalMassProperties,
     velocity: Velocity,
+    gravity_scale: GravityScale,
     friction: Friction,
     restitution: Restitution,
     locked_axes: LockedAxes,
@@ -109,41 +162,35 @@ struct PlayerBundle {
 
 impl Default for PlayerBundle {
     fn default() -> Self {
-        let mut jump_cooldown_timer = Timer::new(Duration::from_millis(200), TimerMode::Once);
-        jump_cooldown_timer.tick(Duration::from_millis(200));
+        let mut jump_cooldown_timer = Timer::new(JUMP_COOLDOWN, TimerMode::Once);
+        jump_cooldown_timer.tick(JUMP_COOLDOWN);
         Self {
             sprite_sheet_bundle: LdtkSpriteSheetBundle::default(),
             render_layer: PLAYER_RENDER_LAYER,
             player_marker: PlayerMarker,
             player_status: PlayerStatus {
                 jump_cooldown: jump_cooldown_timer,
-                coyote_frames: Timer::new(Duration::from_millis(100), TimerMode::Once),
+                coyote_frames: Timer::new(COYOTE_TIME, TimerMode::Once),
                 jump_buffer: {
                     let mut timer = Timer::new(Duration::from_millis(100), TimerMode::Once);
                     timer.tick(Duration::from_millis(100));
                     timer
                 },
+                holding_jump: false,
                 level_finished: false,
                 dead: false,
                 exiting: false,
+                invincible: false,
             },
-            player_inventory: PlayerInventory {
-                num_keys: 0,
-                max_extra_jumps: 0,
-                extra_jumps: 0,
-                air_jumps: 0,
-                wall_jump_cd: [
-                    Timer::from_seconds(0.8, TimerMode::Once),
-                    Timer::from_seconds(0.8, TimerMode::Once),
-                ],
-                on_wall: [false; 2],
-                has_wall_jump: false,
-            },
+            player_inventory: PlayerInventory { num_keys: 0 },
+            player_contacts: PlayerContacts::default(),
+            player_jump: PlayerJump::None,
             rigid_body: RigidBody::Dynamic,
             // collider: Collider::cuboid(5., 5.),
             collider: Collider::round_cuboid(6., 3., 2.),
             mass: AdditionalMassProperties::Mass(50.),
             velocity: Velocity::default(),
+            gravity_scale: GravityScale(1.),
             friction: Friction {
                 coefficient: 0.,
                 combine_rule: CoefficientCombineRule::Min,
@@ -162,7 +209,10 @@ impl Default for PlayerBundle {
     }
 }
 
-fn add_colliders(mut commands: Commands, query: Query<(Entity, &Transform), Added<PlayerMarker>>) {
+pub fn add_colliders(
+    mut commands: Commands,
+    query: Query<(Entity, &Transform), Added<PlayerMarker>>,
+) {
     if let Ok((entity, player_transform)) = query.get_single() {
         commands.entity(entity).remove::<Collider>();
         commands.entity(entity).remove::<Friction>();
@@ -202,25 +252,16 @@ fn add_colliders(mut commands: Commands, query: Query<(Entity, &Transform), Adde
         commands.entity(entity).insert(PlayerCheckpoint {
             transform: player_transform.translation.xy(),
         });
+        commands
+            .entity(entity)
+            .insert(TranslationInterpolation::new(player_transform.translation));
     }
 }
 
-fn update_player_grounded(
+fn update_player_contacts(
     query_player_jump_collider: Query<Entity, With<PlayerJumpColliderMarker>>,
-    mut query_player_wall_collider: Query<
-        (&mut PlayerWallColliderMarker, Entity),
-        With<PlayerWallColliderMarker>,
-    >,
-    mut query_player: Query<
-        (
-            Entity,
-            &mut PlayerInventory,
-            &mut PlayerState,
-            &mut PlayerStatus,
-            &Velocity,
-        ),
-        With<PlayerMarker>,
-    >,
+    query_player_wall_collider: Query<(&PlayerWallColliderMarker, Entity)>,
+    mut query_player: Query<(Entity, &mut PlayerContacts, &mut PlayerStatus), With<PlayerMarker>>,
     query_sensors: Query<
         Entity,
         (
@@ -236,8 +277,7 @@ fn update_player_grounded(
         return;
     };
 
-    let Ok((player_entity, mut player_inventory, mut player_state, mut player_status, velocity)) =
-        query_player.get_single_mut()
+    let Ok((player_entity, mut player_contacts, mut player_status)) = query_player.get_single_mut()
     else {
         return;
     };
@@ -246,46 +286,44 @@ fn update_player_grounded(
     };
 
     // update if the player is touching the wall
-    for (wall_cooldown, wall_collider) in query_player_wall_collider.iter_mut() {
+    for (wall_collider_marker, wall_collider) in query_player_wall_collider.iter() {
         // check the collider to see if it is next to a wall
-        player_inventory.on_wall[wall_cooldown.dir] = false;
-        for (collider_1, collider_2, _) in rapier_context.intersection_pairs_with(wall_collider) {
-            let other_entity = if collider_1 != wall_collider {
+        player_contacts.on_wall[wall_collider_marker.dir] = rapier_context
+            .intersection_pairs_with(wall_collider)
+            .any(|(collider_1, collider_2, _)| {
+                let other_entity = if collider_1 != wall_collider {
+                    collider_1
+                } else {
+                    collider_2
+                };
+                query_sensors.get(other_entity).is_err()
+                    && other_entity != player_entity
+                    && other_entity != backwards_barrier
+            });
+    }
+
+    player_contacts.grounded = rapier_context
+        .intersection_pairs_with(player_jump_collider_entity)
+        .any(|(collider_1, collider_2, _)| {
+            let other_entity = if collider_1 != player_jump_collider_entity {
                 collider_1
             } else {
                 collider_2
             };
-            if query_sensors.get(other_entity).is_err()
-                && other_entity != player_entity
-                && other_entity != backwards_barrier
-            {
-                player_inventory.on_wall[wall_cooldown.dir] = true;
-                if player_inventory.has_wall_jump {
-                    *player_state = PlayerState::Sliding;
-                }
-                // remove the air jumps if hit something
-                player_inventory.air_jumps = 0;
-            }
-        }
+            query_sensors.get(other_entity).is_err()
+        });
+    if player_contacts.grounded {
+        player_status.coyote_frames.reset();
     }
+}
 
-    let mut grounded = false;
-    for (collider_1, collider_2, _) in
-        rapier_context.intersection_pairs_with(player_jump_collider_entity)
-    {
-        let other_entity = if collider_1 != player_jump_collider_entity {
-            collider_1
-        } else {
-            collider_2
-        };
-        if query_sensors.get(other_entity).is_err() {
-            grounded = true;
-            player_status.coyote_frames.reset();
-            player_inventory.extra_jumps = player_inventory.max_extra_jumps;
-            // remove the air jumps if hit something
-            player_inventory.air_jumps = 0;
-        }
-    }
+fn update_player_grounded(
+    mut query_player: Query<(&PlayerContacts, &mut PlayerState, &Velocity), With<PlayerMarker>>,
+) {
+    let Ok((player_contacts, mut player_state, velocity)) = query_player.get_single_mut() else {
+        return;
+    };
+    let grounded = player_contacts.grounded;
 
     if grounded && (*player_state == PlayerState::Falling || *player_state == PlayerState::Sliding)
     {
@@ -293,14 +331,15 @@ fn update_player_grounded(
         *player_state = PlayerState::FallingToIdle;
     } else if !grounded
         && *player_state == PlayerState::Sliding
-        && !(player_inventory.on_wall[0] || player_inventory.on_wall[1])
+        && !(player_contacts.on_wall[0] || player_contacts.on_wall[1])
     {
         *player_state = PlayerState::SlidingToJump;
     } else if !grounded
         && velocity.linvel.y < 0.
         && (*player_state != PlayerState::FallingToIdle
             && *player_state != PlayerState::Sliding
-            && *player_state != PlayerState::SlidingToJump)
+            && *player_state != PlayerState::SlidingToJump
+            && *player_state != PlayerState::Dashing)
     {
         *player_state = PlayerState::Falling;
     }
@@ -322,23 +361,24 @@ pub fn move_player(
         (
             &mut Velocity,
             &mut Sprite,
-            &mut PlayerInventory,
+            &PlayerContacts,
             &mut PlayerStatus,
             &mut PlayerState,
+            &mut PlayerJump,
         ),
         With<PlayerMarker>,
     >,
     camera_panning_state: Res<CameraPanning>,
-    keys: Res<ButtonInput<KeyCode>>,
+    keys: Res<GameplayInput>,
     time: Res<Time>,
-    mut sound_effect_event_writer: EventWriter<SoundEffectEvent>,
 ) {
     if let Ok((
         mut player_velocity,
         mut sprite,
-        mut player_inventory,
+        player_contacts,
         mut player_status,
         mut player_state,
+        mut player_jump,
     )) = query_player.get_single_mut()
     {
         if !player_status.jump_cooldown.finished() {
@@ -346,18 +386,10 @@ pub fn move_player(
             // player_status.grounded = false;
             // *player_state = PlayerState::Jumping;
         }
-        let mut on_wall = false;
-        for i in 0..2 {
-            if !player_inventory.wall_jump_cd[i].finished() {
-                player_inventory.wall_jump_cd[i].tick(time.delta());
-            }
-            if player_inventory.on_wall[i] {
-                on_wall = true;
-            }
-        }
+        let on_wall = player_contacts.on_wall.contains(&true);
         // println!("state: {:?}", *player_state);
         // player_velocity.linvel = Vec2::ZERO;
-        const VELOCITY: Vec2 = Vec2::new(55., 0.);
+        const VELOCITY: Vec2 = Vec2::new(RUN_ACCELERATION, 0.);
         let mut moved = false;
         if player_status.dead
             || player_status.level_finished
@@ -393,9 +425,9 @@ pub fn move_player(
         }
 
         // hack
-        if player_inventory.on_wall[0] {
+        if player_contacts.on_wall[0] {
             sprite.flip_x = true;
-        } else if player_inventory.on_wall[1] {
+        } else if player_contacts.on_wall[1] {
             sprite.flip_x = false;
         }
 
@@ -412,75 +444,73 @@ pub fn move_player(
             if keys.just_pressed(KeyCode::ArrowUp) {
                 player_status.jump_buffer.reset();
             }
-            let mut can_jump = false;
-            let mut wall_jump = false;
             if *player_state != PlayerState::Jumping
                 && *player_state != PlayerState::Falling
                 && *player_state != PlayerState::SlidingToJump
                 && *player_state != PlayerState::Sliding
+                && *player_state != PlayerState::Dashing
             {
                 // jump from floor
-                can_jump = true;
+                *player_jump = PlayerJump::Jump;
             } else if !player_status.coyote_frames.finished() {
-                can_jump = true;
-            } else if player_inventory.has_wall_jump
-                && player_inventory.on_wall[0]
-                && player_inventory.wall_jump_cd[0].finished()
-            {
-                // wall jump from left wall
-                can_jump = true;
-                wall_jump = true;
-                player_inventory.wall_jump_cd[0].reset();
-                // if they use the wall jump, reset their double jump
-                // println!("wall jump");
-                player_inventory.extra_jumps = player_inventory.max_extra_jumps;
-            } else if player_inventory.has_wall_jump
-                && player_inventory.on_wall[1]
-                && player_inventory.wall_jump_cd[1].finished()
-            {
-                // wall jump from right wall
-                can_jump = true;
-                wall_jump = true;
-                player_inventory.wall_jump_cd[1].reset();
-                // println!("wall jump");
-                // if they use the wall jump, reset their double jump
-                player_inventory.extra_jumps = player_inventory.max_extra_jumps;
-            } else if player_inventory.extra_jumps >= 1 {
-                // jump in air with double jump
-                can_jump = true;
-                player_inventory.extra_jumps -= 1;
-            } else if player_inventory.air_jumps >= 1 {
-                // jump in air with jump token
-                can_jump = true;
-                player_inventory.air_jumps -= 1;
-            }
-
-            // ugly but i wrote it like this so i can print debug messages
-            if can_jump {
-                sound_effect_event_writer.send(SoundEffectEvent(SoundEffectType::Jump));
-                player_velocity.linvel.y = 130.;
-                if wall_jump {
-                    *player_state = PlayerState::SlidingToJump;
-                } else {
-                    *player_state = PlayerState::Jumping;
-                }
-                player_status.jump_cooldown.reset();
+                *player_jump = PlayerJump::Jump;
+            } else {
+                // up to the abilities
+                *player_jump = PlayerJump::Wanted;
             }
         }
 
-        // allow player to slide down walls if they have wall jump
-        if on_wall && player_inventory.has_wall_jump && player_velocity.linvel.y < -75. {
-            player_velocity.linvel.y = -75.;
-        }
-
-        player_velocity.linvel.x /= 1.6;
+        player_velocity.linvel.x /= RUN_DRAG;
         if player_velocity.linvel.x.abs() < 0.1 {
             player_velocity.linvel.x = 0.;
         }
     }
 }
 
+/// Makes the jump the player or one of their abilities decided on this tick
+fn apply_jump(
+    mut query_player: Query<
+        (
+            &mut PlayerJump,
+            &mut Velocity,
+            &mut PlayerStatus,
+            &mut PlayerState,
+        ),
+        With<PlayerMarker>,
+    >,
+    jump_tuning: Res<JumpTuning>,
+    mut sound_effect_event_writer: EventWriter<SoundEffectEvent>,
+) {
+    let Ok((mut player_jump, mut player_velocity, mut player_status, mut player_state)) =
+        query_player.get_single_mut()
+    else {
+        return;
+    };
+    match std::mem::take(&mut *player_jump) {
+        PlayerJump::Jump => *player_state = PlayerState::Jumping,
+        PlayerJump::WallJump => *player_state = PlayerState::SlidingToJump,
+        PlayerJump::None | PlayerJump::Wanted => return,
+    }
+    sound_effect_event_writer.send(SoundEffectEvent(SoundEffectType::Jump));
+    player_velocity.linvel.y = jump_tuning.velocity;
+    player_status.jump_cooldown.reset();
+    player_status.holding_jump = true;
+}
+
+/// Whether the player can move, which they can't while dead, done with the level or watching the
+/// camera pan
+fn player_in_control(
+    query_player: Query<&PlayerStatus, With<PlayerMarker>>,
+    camera_panning_state: Res<CameraPanning>,
+) -> bool {
+    query_player
+        .get_single()
+        .is_ok_and(|player_status| !player_status.dead && !player_status.level_finished)
+        && camera_panning_state.panning_state == CameraPanningState::WaitingAtPlayer
+}
+
 // TODO: split camera looping and player looping into separate systems
+/// Wraps the player around the edges of the level, counting a cycle every time they wrap forward
 pub fn loop_player(
     mut query_player_camera: Query<
         &mut Transform,
@@ -488,29 +518,44 @@ pub fn loop_player(
     >,
     mut query_player: Query<&mut Transform, With<PlayerMarker>>,
     query_level: Query<&LayerMetadata>,
+    level_wrap: Res<LevelWrap>,
+    mut cycle_count: ResMut<CycleCount>,
 ) {
     let Ok(mut player_transform) = query_player.get_single_mut() else {
         return;
     };
-    let Ok(mut camera_transform) = query_player_camera.get_single_mut() else {
-        return;
-    };
+    // there's no camera to take along when running without a window
+    let mut camera_transform = query_player_camera.get_single_mut().ok();
     for level in query_level.iter() {
         if level.layer_instance_type != bevy_ecs_ldtk::ldtk::Type::IntGrid {
             continue;
         }
 
-        let width = level.c_wid as f32 * 16.;
-        if player_transform.translation.x < 0. {
-            player_transform.translation.x += width;
-            camera_transform.translation.x += width;
-            // println!(
-            //     "looped camera transform is {}",
-            //     camera_transform.translation.x
-            // )
-        } else if player_transform.translation.x > width {
-            player_transform.translation.x -= width;
-            camera_transform.translation.x -= width;
+        let mut delta = Vec3::ZERO;
+        if level_wrap.0.horizontal() {
+            let width = level.c_wid as f32 * 16.;
+            if player_transform.translation.x < 0. {
+                delta.x = width;
+            } else if player_transform.translation.x > width {
+                delta.x = -width;
+            }
+        }
+        if level_wrap.0.vertical() {
+            let height = level.c_hei as f32 * 16.;
+            if player_transform.translation.y < 0. {
+                delta.y = height;
+            } else if player_transform.translation.y > height {
+                delta.y = -height;
+            }
+        }
+        // only wrapping forward along the scroll axis starts a new cycle, so crossing a seam back
+        // and forth doesn't count
+        if delta[level_wrap.0.scroll_axis()] < 0. {
+            cycle_count.0 += 1;
+        }
+        player_transform.translation += delta;
+        if let Some(camera_transform) = camera_transform.as_mut() {
+            camera_transform.translation += delta;
         }
     }
 }
@@ -523,6 +568,10 @@ pub struct PlayerCheckpoint {
 #[derive(Event)]
 pub struct SetCheckpointEvent;
 
+/// Sent once when the player reaches the goal of the current level
+#[derive(Event)]
+pub struct LevelCompletedEvent;
+
 fn set_player_checkpoint(
     mut query_player: Query<(&mut PlayerCheckpoint, &Transform), With<PlayerMarker>>,
     mut checkpoint_events: EventReader<SetCheckpointEvent>,
@@ -545,7 +594,7 @@ pub fn kill_player(
         With<SpikeMarker>,
     >,
     rapier_context: Res<RapierContext>,
-    keys: Res<ButtonInput<KeyCode>>,
+    keys: Res<GameplayInput>,
     mut sound_effect_event_writer: EventWriter<SoundEffectEvent>,
 ) {
     let Ok(mut player_status) = query_player.get_single_mut() else {
@@ -559,7 +608,7 @@ pub fn kill_player(
     let mut spike_kill = false;
     if keys.just_pressed(KeyCode::KeyR) {
         kill_player = true;
-    } else {
+    } else if !player_status.invincible {
         for hazard in query_hazards.iter() {
             if rapier_context.intersection_pair(player_collider, hazard) == Some(true) {
                 kill_player = true;
@@ -590,7 +639,7 @@ pub fn kill_player(
 }
 
 fn exit_level(
-    keys: Res<ButtonInput<KeyCode>>,
+    keys: Res<GameplayInput>,
     mut query_player: Query<&mut PlayerStatus, With<PlayerMarker>>,
 ) {
     let Ok(mut player_status) = query_player.get_single_mut() else {
@@ -0,0 +1,274 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    camera::{CameraPanning, CameraPanningState},
+    input::{GameplayInput, GameplayInputSet},
+    level::{manifest::LevelManifest, FromLevelSelect},
+    save::SaveDirectory,
+    state::{LevelLoadingState, TargetLevel, TargetPack},
+};
+
+/// Version written to new replay files
+pub const REPLAY_VERSION: u32 = 3;
+
+/// Command line flag that plays back a replay file instead of reading the keyboard
+pub(crate) const REPLAY_FLAG: &str = "--replay=";
+
+/// Folder inside the save directory recordings are written to
+const REPLAY_DIRECTORY_NAME: &str = "replays";
+
+/// File the recording of the last played level is written to, ready to be attached to bug reports
+const LAST_REPLAY_FILE_NAME: &str = "last.replay.ron";
+
+pub struct ReplayManagementPlugin {
+    /// Replay file to play back once the level manifest has loaded
+    pub replay_path: Option<PathBuf>,
+}
+
+impl ReplayManagementPlugin {
+    /// Reads the replay path from the `--replay=<path>` flag in `args`
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Self {
+        Self {
+            replay_path: args.find_map(|arg| arg.strip_prefix(REPLAY_FLAG).map(PathBuf::from)),
+        }
+    }
+}
+
+impl Plugin for ReplayManagementPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GameRng(StdRng::from_entropy()))
+            .init_resource::<InputRecorder>()
+            .add_systems(OnEnter(LevelLoadingState::Loading), start_recording)
+            .add_systems(
+                OnExit(LevelLoadingState::Loaded),
+                (write_recording, end_replay),
+            )
+            .add_systems(
+                FixedPreUpdate,
+                (replay_input, record_input.run_if(player_in_control))
+                    .chain()
+                    .after(GameplayInputSet)
+                    .run_if(in_state(LevelLoadingState::Loaded)),
+            )
+            .add_systems(
+                Update,
+                start_replay
+                    .run_if(resource_exists::<InputReplay>)
+                    .run_if(in_state(LevelLoadingState::MainMenu)),
+            );
+
+        if let Some(replay_path) = &self.replay_path {
+            match read_replay(replay_path) {
+                Ok(recording) => {
+                    app.insert_resource(InputReplay {
+                        recording,
+                        tick: 0,
+                        started: false,
+                    });
+                }
+                Err(e) => warn!("could not load replay {}: {e}", replay_path.display()),
+            }
+        }
+    }
+}
+
+/// Source of randomness for anything that should play out the same way in a replay. Reseeded
+/// every time a level loads.
+#[derive(Resource)]
+pub struct GameRng(pub StdRng);
+
+#[derive(Debug, Error)]
+pub enum ReplayError {
+    #[error("could not access replay file: {0}")]
+    Io(#[from] io::Error),
+    #[error("could not parse replay file: {0}")]
+    Parse(#[from] ron::error::SpannedError),
+    #[error("could not serialize replay file: {0}")]
+    Serialize(#[from] ron::Error),
+    #[error("replay file version {0} is not supported")]
+    UnsupportedVersion(u32),
+}
+
+/// The gameplay input of one attempt at a level, from the moment the player gains control
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct InputRecording {
+    pub version: u32,
+    pub pack_id: String,
+    pub level_iid: String,
+    /// Seed [`GameRng`] was given when the level loaded
+    pub seed: u64,
+    pub ticks: Vec<InputTick>,
+}
+
+/// The input of a single fixed tick, in the format of [`GameplayInput::set`]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct InputTick {
+    pub keys: u8,
+    pub presses: u8,
+}
+
+/// Recording of the level currently being played
+#[derive(Resource, Default)]
+pub struct InputRecorder(pub InputRecording);
+
+/// A recording being played back instead of the keyboard
+#[derive(Resource)]
+pub struct InputReplay {
+    pub recording: InputRecording,
+    /// Index of the next tick to play back
+    pub tick: usize,
+    /// Whether the replayed level has been loaded
+    started: bool,
+}
+
+/// Reads the replay file at `path`
+pub fn read_replay(path: &Path) -> Result<InputRecording, ReplayError> {
+    let contents = fs::read_to_string(path)?;
+    let recording: InputRecording = ron::from_str(&contents)?;
+    if recording.version != REPLAY_VERSION {
+        return Err(ReplayError::UnsupportedVersion(recording.version));
+    }
+    Ok(recording)
+}
+
+/// Writes `recording` to `path`, creating its directory if needed
+pub fn write_replay(path: &Path, recording: &InputRecording) -> Result<(), ReplayError> {
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+    fs::write(path, ron::to_string(recording)?)?;
+    Ok(())
+}
+
+/// Input is only recorded and replayed once the intro camera pan has finished, since the pan
+/// runs on frame time and can take a different number of ticks each time
+fn player_in_control(camera_panning: Res<CameraPanning>) -> bool {
+    camera_panning.panning_state == CameraPanningState::WaitingAtPlayer
+}
+
+fn start_recording(
+    mut input_recorder: ResMut<InputRecorder>,
+    mut game_rng: ResMut<GameRng>,
+    input_replay: Option<Res<InputReplay>>,
+    target_pack: Res<TargetPack>,
+    target_level: Res<TargetLevel>,
+    level_manifest: Res<LevelManifest>,
+) {
+    let seed = match &input_replay {
+        Some(input_replay) => input_replay.recording.seed,
+        None => rand::random(),
+    };
+    game_rng.0 = StdRng::seed_from_u64(seed);
+    let (pack_id, level_iid) = level_manifest
+        .level(target_pack.0, target_level.0)
+        .map(|(pack, level)| (pack.id.clone(), level.iid.clone()))
+        .unwrap_or_default();
+    input_recorder.0 = InputRecording {
+        version: REPLAY_VERSION,
+        pack_id,
+        level_iid,
+        seed,
+        ticks: Vec::new(),
+    };
+}
+
+fn record_input(gameplay_input: Res<GameplayInput>, mut input_recorder: ResMut<InputRecorder>) {
+    input_recorder.0.ticks.push(InputTick {
+        keys: gameplay_input.keys(),
+        presses: gameplay_input.presses(),
+    });
+}
+
+fn write_recording(
+    input_recorder: Res<InputRecorder>,
+    input_replay: Option<Res<InputReplay>>,
+    save_directory: Res<SaveDirectory>,
+) {
+    if input_replay.is_some() || input_recorder.0.ticks.is_empty() {
+        return;
+    }
+    let Some(directory) = &save_directory.0 else {
+        return;
+    };
+    let path = directory
+        .join(REPLAY_DIRECTORY_NAME)
+        .join(LAST_REPLAY_FILE_NAME);
+    if let Err(e) = write_replay(&path, &input_recorder.0) {
+        warn!("could not write replay: {e}");
+    }
+}
+
+/// Loads the replayed level once the level manifest is available
+fn start_replay(
+    mut input_replay: ResMut<InputReplay>,
+    level_manifest: Res<LevelManifest>,
+    mut target_pack: ResMut<TargetPack>,
+    mut target_level: ResMut<TargetLevel>,
+    mut from_level_select: ResMut<FromLevelSelect>,
+    mut next_state: ResMut<NextState<LevelLoadingState>>,
+) {
+    if input_replay.started {
+        return;
+    }
+    let recording = &input_replay.recording;
+    let Some((pack_index, level_index)) =
+        level_manifest
+            .packs
+            .iter()
+            .enumerate()
+            .find_map(|(pack_index, pack)| {
+                let level_index = pack
+                    .levels
+                    .iter()
+                    .position(|level| level.iid == recording.level_iid)?;
+                (pack.id == recording.pack_id).then_some((pack_index, level_index))
+            })
+    else {
+        return;
+    };
+    target_pack.0 = pack_index;
+    target_level.0 = level_index;
+    // go back to the level select afterwards instead of continuing with the next level
+    from_level_select.0 = true;
+    input_replay.started = true;
+    next_state.set(LevelLoadingState::Loading);
+}
+
+/// Replaces the gameplay input with the next tick of the replay
+fn replay_input(
+    mut commands: Commands,
+    mut gameplay_input: ResMut<GameplayInput>,
+    input_replay: Option<ResMut<InputReplay>>,
+    camera_panning: Res<CameraPanning>,
+) {
+    let Some(mut input_replay) = input_replay.filter(|input_replay| input_replay.started) else {
+        return;
+    };
+    if camera_panning.panning_state != CameraPanningState::WaitingAtPlayer {
+        // keep the keyboard out of the replay until it starts
+        gameplay_input.set(0, 0);
+        return;
+    }
+    let Some(tick) = input_replay.recording.ticks.get(input_replay.tick).copied() else {
+        info!("replay finished");
+        commands.remove_resource::<InputReplay>();
+        return;
+    };
+    input_replay.tick += 1;
+    gameplay_input.set(tick.keys, tick.presses);
+}
+
+/// Stops a replay once the replayed level is left
+fn end_replay(mut commands: Commands, input_replay: Option<Res<InputReplay>>) {
+    if input_replay.is_some_and(|input_replay| input_replay.started) {
+        commands.remove_resource::<InputReplay>();
+    }
+}
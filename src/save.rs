@@ -0,0 +1,273 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use bevy::{ecs::system::SystemParam, prelude::*, time::Stopwatch};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    level::{BestTimes, LastAccessibleLevel},
+    menus::{CycleCount, DeathCount, SpeedrunTimer},
+    state::LevelLoadingState,
+};
+
+/// Version written to new save files. Bump this and add a migration to [`migrate_save`] whenever
+/// the layout of [`SaveData`] changes.
+pub const SAVE_VERSION: u32 = 1;
+
+/// Number of independent save slots the player can pick from
+pub const SAVE_SLOT_COUNT: usize = 3;
+
+pub struct SaveManagementPlugin {
+    /// Directory the save files are kept in. Nothing is loaded or saved if this is `None`.
+    pub directory: Option<PathBuf>,
+}
+
+impl Default for SaveManagementPlugin {
+    fn default() -> Self {
+        Self {
+            directory: dirs::data_dir().map(|dir| dir.join("dungeon-deja-vu")),
+        }
+    }
+}
+
+impl Plugin for SaveManagementPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SaveDirectory(self.directory.clone()))
+            .insert_resource(ActiveSaveSlot(0))
+            .insert_resource(SaveSlots(vec![SaveSlot::Empty; SAVE_SLOT_COUNT]))
+            // only saved when progress can have changed, so opening a menu doesn't fill an empty
+            // slot or bring back a deleted one
+            .add_systems(OnEnter(LevelLoadingState::Loading), write_save)
+            .add_systems(OnEnter(LevelLoadingState::EndScreen), write_save)
+            .add_systems(
+                Last,
+                write_save
+                    .run_if(on_event::<AppExit>())
+                    .run_if(in_state(LevelLoadingState::Loaded)),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        // every other plugin has inserted its default resources by now, so the saved values
+        // overwrite them before the main menu is shown
+        let Some(directory) = &self.directory else {
+            return;
+        };
+        let save_slots = load_save_slots(directory);
+        let active_save_slot = app.world().resource::<ActiveSaveSlot>().0;
+        if let SaveSlot::Saved(save_data) = save_slots[active_save_slot].clone() {
+            save_data.apply(app.world_mut());
+        }
+        app.insert_resource(SaveSlots(save_slots));
+    }
+}
+
+#[derive(Resource)]
+pub struct SaveDirectory(pub Option<PathBuf>);
+
+/// Index of the save slot progress is currently loaded from and saved to
+#[derive(Resource)]
+pub struct ActiveSaveSlot(pub usize);
+
+/// The contents of every save slot
+#[derive(Resource)]
+pub struct SaveSlots(pub Vec<SaveSlot>);
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum SaveSlot {
+    #[default]
+    Empty,
+    Saved(SaveData),
+    /// A save file that couldn't be read, with why. It's never written over, only deleted by the
+    /// player, so a file from a newer version of the game or one that can be fixed by hand isn't
+    /// lost.
+    Unreadable(String),
+}
+
+impl SaveSlot {
+    pub fn save_data(&self) -> Option<&SaveData> {
+        match self {
+            SaveSlot::Saved(save_data) => Some(save_data),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SaveError {
+    #[error("could not access save file: {0}")]
+    Io(#[from] io::Error),
+    #[error("could not parse save file: {0}")]
+    Parse(#[from] ron::error::SpannedError),
+    #[error("could not serialize save file: {0}")]
+    Serialize(#[from] ron::Error),
+    #[error("save file version {0} is newer than this game supports")]
+    NewerVersion(u32),
+    #[error("save file version {0} is older than any this game can migrate")]
+    UnknownVersion(u32),
+}
+
+/// Everything that is kept between launches of the game
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SaveData {
+    pub version: u32,
+    pub last_accessible_level: HashMap<String, usize>,
+    pub best_times: HashMap<String, HashMap<String, f32>>,
+    pub run: RunProgress,
+}
+
+/// Stats of the run in progress, which start over with a new game
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct RunProgress {
+    pub death_count: usize,
+    pub cycle_count: usize,
+    /// Elapsed time of the run in seconds
+    pub speedrun_time: f32,
+}
+
+impl Default for SaveData {
+    fn default() -> Self {
+        Self {
+            version: SAVE_VERSION,
+            last_accessible_level: HashMap::new(),
+            best_times: HashMap::new(),
+            run: RunProgress::default(),
+        }
+    }
+}
+
+impl SaveData {
+    /// Replaces the game's progress resources with the contents of this save
+    pub fn apply(self, world: &mut World) {
+        let mut speedrun_timer = Stopwatch::new();
+        speedrun_timer.set_elapsed(Duration::from_secs_f32(self.run.speedrun_time));
+        world.insert_resource(LastAccessibleLevel(self.last_accessible_level));
+        world.insert_resource(DeathCount(self.run.death_count));
+        world.insert_resource(CycleCount(self.run.cycle_count));
+        world.insert_resource(SpeedrunTimer(speedrun_timer));
+        world.insert_resource(BestTimes(self.best_times));
+    }
+}
+
+/// Only the version of a save file, read first to decide how to parse the rest
+#[derive(Deserialize)]
+struct SaveVersion {
+    version: u32,
+}
+
+fn save_slot_path(directory: &Path, slot: usize) -> PathBuf {
+    directory.join(format!("save_{}.ron", slot + 1))
+}
+
+/// Reads every save slot in `directory`
+pub fn load_save_slots(directory: &Path) -> Vec<SaveSlot> {
+    (0..SAVE_SLOT_COUNT)
+        .map(|slot| match read_save(directory, slot) {
+            Ok(Some(save_data)) => SaveSlot::Saved(save_data),
+            Ok(None) => SaveSlot::Empty,
+            Err(e) => {
+                error!(
+                    "could not load save slot {}, it won't be saved over: {e}",
+                    slot + 1
+                );
+                SaveSlot::Unreadable(e.to_string())
+            }
+        })
+        .collect()
+}
+
+/// Reads save slot `slot` in `directory`, returning `None` if the slot is empty
+pub fn read_save(directory: &Path, slot: usize) -> Result<Option<SaveData>, SaveError> {
+    let contents = match fs::read_to_string(save_slot_path(directory, slot)) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let SaveVersion { version } = ron::from_str(&contents)?;
+    migrate_save(version, &contents).map(Some)
+}
+
+/// Parses a save file written by `version` of the game into the current [`SaveData`]
+fn migrate_save(version: u32, contents: &str) -> Result<SaveData, SaveError> {
+    match version {
+        SAVE_VERSION => Ok(ron::from_str(contents)?),
+        version if version > SAVE_VERSION => Err(SaveError::NewerVersion(version)),
+        _ => Err(SaveError::UnknownVersion(version)),
+    }
+}
+
+/// Writes `save_data` to save slot `slot` in `directory`, creating the directory if needed
+pub fn write_save_file(
+    directory: &Path,
+    slot: usize,
+    save_data: &SaveData,
+) -> Result<(), SaveError> {
+    fs::create_dir_all(directory)?;
+    let contents = ron::ser::to_string_pretty(save_data, ron::ser::PrettyConfig::default())?;
+    // write to a temporary file first so a crash mid-write can't corrupt the existing save
+    let save_path = save_slot_path(directory, slot);
+    let temp_path = save_path.with_extension("ron.tmp");
+    fs::write(&temp_path, contents)?;
+    fs::rename(temp_path, save_path)?;
+    Ok(())
+}
+
+/// Empties save slot `slot` in `directory`
+pub fn delete_save_file(directory: &Path, slot: usize) -> Result<(), SaveError> {
+    match fs::remove_file(save_slot_path(directory, slot)) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// The stats of the run in progress, as saved in [`RunProgress`]
+#[derive(SystemParam)]
+struct RunStats<'w> {
+    death_count: Res<'w, DeathCount>,
+    cycle_count: Res<'w, CycleCount>,
+    speedrun_timer: Res<'w, SpeedrunTimer>,
+}
+
+impl RunStats<'_> {
+    fn progress(&self) -> RunProgress {
+        RunProgress {
+            death_count: self.death_count.0,
+            cycle_count: self.cycle_count.0,
+            speedrun_time: self.speedrun_timer.0.elapsed_secs(),
+        }
+    }
+}
+
+fn write_save(
+    save_directory: Res<SaveDirectory>,
+    active_save_slot: Res<ActiveSaveSlot>,
+    mut save_slots: ResMut<SaveSlots>,
+    last_accessible_level: Res<LastAccessibleLevel>,
+    best_times: Res<BestTimes>,
+    run_stats: RunStats,
+) {
+    if let SaveSlot::Unreadable(e) = &save_slots.0[active_save_slot.0] {
+        error!(
+            "not saving over save slot {}, which couldn't be loaded: {e}",
+            active_save_slot.0 + 1
+        );
+        return;
+    }
+    let save_data = SaveData {
+        version: SAVE_VERSION,
+        last_accessible_level: last_accessible_level.0.clone(),
+        best_times: best_times.0.clone(),
+        run: run_stats.progress(),
+    };
+    if let Some(directory) = &save_directory.0 {
+        if let Err(e) = write_save_file(directory, active_save_slot.0, &save_data) {
+            error!("could not write save slot {}: {e}", active_save_slot.0 + 1);
+        }
+    }
+    save_slots.0[active_save_slot.0] = SaveSlot::Saved(save_data);
+}
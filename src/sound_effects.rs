@@ -3,12 +3,16 @@ use bevy::{
     prelude::*,
 };
 
-pub struct SoundEffectsManagementPlugin;
+#[derive(Default)]
+pub struct SoundEffectsManagementPlugin {
+    /// Starts with the music and sound effects muted, until M is pressed
+    pub muted: bool,
+}
 
 impl Plugin for SoundEffectsManagementPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<SoundEffectEvent>()
-            .insert_resource(AudioMuted(false))
+            .insert_resource(AudioMuted(self.muted))
             .add_systems(Startup, start_music)
             .add_systems(
                 Update,
@@ -24,7 +28,7 @@ impl Plugin for SoundEffectsManagementPlugin {
 #[derive(Resource)]
 struct AudioMuted(bool);
 
-#[derive(Component, PartialEq, Eq)]
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SoundEffectType {
     Jump,
     SmallPowerup,
@@ -77,13 +81,13 @@ fn play_sound_effect(
     }
 }
 
-fn start_music(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn start_music(mut commands: Commands, asset_server: Res<AssetServer>, muted: Res<AudioMuted>) {
     commands
         .spawn(AudioBundle {
             source: asset_server.load("music/far_from_shore.wav"),
             settings: PlaybackSettings {
                 mode: PlaybackMode::Loop,
-                volume: Volume::new(0.5),
+                volume: Volume::new(if muted.0 { 0. } else { 0.5 }),
                 ..default()
             },
         })
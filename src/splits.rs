@@ -0,0 +1,426 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::prelude::*;
+use quick_xml::escape::escape;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    level::{BestTimes, RunSplits},
+    menus::{CycleCount, DeathCount},
+    save::SaveDirectory,
+    state::LevelLoadingState,
+};
+
+const GAME_NAME: &str = "Dungeon Deja Vu";
+const CATEGORY_NAME: &str = "Any%";
+
+/// Name LiveSplit gives the comparison of the runner's personal best
+const PERSONAL_BEST_COMPARISON: &str = "Personal Best";
+
+/// File in the save directory an imported `.lss` is copied to, so it is compared against on every
+/// launch until another one is imported
+const COMPARISON_FILE_NAME: &str = "comparison.lss";
+
+/// Folder inside the save directory exported splits are written to
+const SPLITS_DIRECTORY_NAME: &str = "splits";
+
+pub struct SplitsManagementPlugin;
+
+impl Plugin for SplitsManagementPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ExportedSplits>()
+            .init_resource::<ComparisonSplits>()
+            .add_systems(Startup, load_comparison_splits)
+            .add_systems(OnEnter(LevelLoadingState::EndScreen), export_splits)
+            .add_systems(Update, import_dropped_splits);
+    }
+}
+
+/// Path of the `.lss` file the last finished run was exported to
+#[derive(Resource, Default)]
+pub struct ExportedSplits(pub Option<PathBuf>);
+
+/// Splits of an imported run that the current run is compared against
+#[derive(Resource, Default)]
+pub struct ComparisonSplits(pub Vec<ComparisonSplit>);
+
+impl ComparisonSplits {
+    /// Time into the compared run at which the level called `level_name` was finished
+    pub fn run_time(&self, level_name: &str) -> Option<f32> {
+        self.0
+            .iter()
+            .find(|split| split.level_name == level_name)
+            .map(|split| split.run_time)
+    }
+}
+
+pub struct ComparisonSplit {
+    pub level_name: String,
+    /// Time into the compared run at which the level was finished, in seconds
+    pub run_time: f32,
+}
+
+#[derive(Debug, Error)]
+pub enum SplitsError {
+    #[error("could not access splits file: {0}")]
+    Io(#[from] io::Error),
+    #[error("could not parse splits file: {0}")]
+    Xml(#[from] quick_xml::DeError),
+    #[error("invalid time {0:?} in splits file")]
+    InvalidTime(String),
+}
+
+/// The parts of a LiveSplit `.lss` file needed to compare against it
+#[derive(Deserialize)]
+struct LssRun {
+    #[serde(rename = "Segments")]
+    segments: LssSegments,
+}
+
+#[derive(Deserialize)]
+struct LssSegments {
+    #[serde(rename = "Segment", default)]
+    segments: Vec<LssSegment>,
+}
+
+#[derive(Deserialize)]
+struct LssSegment {
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "SplitTimes")]
+    split_times: LssSplitTimes,
+}
+
+#[derive(Deserialize)]
+struct LssSplitTimes {
+    #[serde(rename = "SplitTime", default)]
+    split_times: Vec<LssSplitTime>,
+}
+
+#[derive(Deserialize)]
+struct LssSplitTime {
+    #[serde(rename = "@name")]
+    name: String,
+    #[serde(rename = "GameTime")]
+    game_time: Option<String>,
+    #[serde(rename = "RealTime")]
+    real_time: Option<String>,
+}
+
+/// Formats a time in seconds as a LiveSplit time span, e.g. `00:02:05.3100000`
+pub fn format_lss_time(seconds: f32) -> String {
+    let seconds = seconds.max(0.) as f64;
+    let hours = (seconds / 3600.) as u64;
+    let minutes = (seconds / 60.) as u64 % 60;
+    format!("{:02}:{:02}:{:010.7}", hours, minutes, seconds % 60.)
+}
+
+/// Parses a LiveSplit time span like `00:02:05.3100000` into seconds
+fn parse_lss_time(time: &str) -> Result<f32, SplitsError> {
+    let invalid_time = || SplitsError::InvalidTime(time.to_string());
+    let mut seconds = 0.;
+    for part in time.trim().split(':') {
+        let part: f64 = part.parse().map_err(|_| invalid_time())?;
+        seconds = seconds * 60. + part;
+    }
+    Ok(seconds as f32)
+}
+
+/// Reads the personal best of every segment in the `.lss` file at `path`. Segments without a
+/// personal best are left out.
+pub fn read_lss(path: &Path) -> Result<Vec<ComparisonSplit>, SplitsError> {
+    let contents = fs::read_to_string(path)?;
+    let run: LssRun = quick_xml::de::from_str(&contents)?;
+    let mut splits = Vec::new();
+    for segment in run.segments.segments {
+        let Some(split_time) = segment
+            .split_times
+            .split_times
+            .into_iter()
+            .find(|split_time| split_time.name == PERSONAL_BEST_COMPARISON)
+        else {
+            continue;
+        };
+        let Some(time) = split_time.game_time.or(split_time.real_time) else {
+            continue;
+        };
+        splits.push(ComparisonSplit {
+            level_name: segment.name,
+            run_time: parse_lss_time(&time)?,
+        });
+    }
+    Ok(splits)
+}
+
+/// Builds a `.lss` file for `run_splits`, using the run itself as the personal best and the best
+/// times as the best segments
+fn write_lss(
+    run_splits: &RunSplits,
+    best_times: &BestTimes,
+    death_count: usize,
+    cycle_count: usize,
+) -> String {
+    let mut segments = String::new();
+    for split in run_splits.0.iter() {
+        let best_time = best_times
+            .get(&split.pack_id, &split.level_iid)
+            .unwrap_or(split.level_time);
+        segments.push_str(&format!(
+            r#"    <Segment>
+      <Name>{}</Name>
+      <Icon />
+      <SplitTimes>
+        <SplitTime name="{PERSONAL_BEST_COMPARISON}">
+          <GameTime>{}</GameTime>
+        </SplitTime>
+      </SplitTimes>
+      <BestSegmentTime>
+        <GameTime>{}</GameTime>
+      </BestSegmentTime>
+      <SegmentHistory />
+    </Segment>
+"#,
+            escape(&split.level_name),
+            format_lss_time(split.run_time),
+            format_lss_time(best_time),
+        ));
+    }
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<Run version="1.7.0">
+  <GameIcon />
+  <GameName>{GAME_NAME}</GameName>
+  <CategoryName>{CATEGORY_NAME}</CategoryName>
+  <Metadata>
+    <Run id="" />
+    <Platform usesEmulator="False"></Platform>
+    <Region></Region>
+    <Variables>
+      <Variable name="Deaths">{death_count}</Variable>
+      <Variable name="Cycles">{cycle_count}</Variable>
+    </Variables>
+  </Metadata>
+  <Offset>00:00:00</Offset>
+  <AttemptCount>1</AttemptCount>
+  <AttemptHistory />
+  <Segments>
+{segments}  </Segments>
+  <AutoSplitterSettings />
+</Run>
+"#
+    )
+}
+
+/// Builds a CSV file with one row per level of `run_splits`
+fn write_csv(run_splits: &RunSplits) -> String {
+    let mut csv = String::from("level,pack,level_time,run_time,deaths,cycles\n");
+    for split in run_splits.0.iter() {
+        csv.push_str(&format!(
+            "\"{}\",{},{:.3},{:.3},{},{}\n",
+            split.level_name.replace('"', "\"\""),
+            split.pack_id,
+            split.level_time,
+            split.run_time,
+            split.deaths,
+            split.cycles,
+        ));
+    }
+    csv
+}
+
+/// Writes the finished run to a `.lss` and a `.csv` file in the splits folder of the save
+/// directory, remembering the path in [`ExportedSplits`]
+pub fn export_splits(
+    save_directory: Res<SaveDirectory>,
+    run_splits: Res<RunSplits>,
+    best_times: Res<BestTimes>,
+    death_count: Res<DeathCount>,
+    cycle_count: Res<CycleCount>,
+    mut exported_splits: ResMut<ExportedSplits>,
+) {
+    exported_splits.0 = None;
+    let Some(directory) = &save_directory.0 else {
+        return;
+    };
+    if run_splits.0.is_empty() {
+        return;
+    }
+    let directory = directory.join(SPLITS_DIRECTORY_NAME);
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    let lss_path = directory.join(format!("run_{timestamp}.lss"));
+    let csv_path = lss_path.with_extension("csv");
+    let result = fs::create_dir_all(&directory)
+        .and_then(|_| {
+            fs::write(
+                &lss_path,
+                write_lss(&run_splits, &best_times, death_count.0, cycle_count.0),
+            )
+        })
+        .and_then(|_| fs::write(&csv_path, write_csv(&run_splits)));
+    match result {
+        Ok(()) => exported_splits.0 = Some(lss_path),
+        Err(e) => warn!("could not export splits: {e}"),
+    }
+}
+
+fn load_comparison_splits(
+    save_directory: Res<SaveDirectory>,
+    mut comparison_splits: ResMut<ComparisonSplits>,
+) {
+    let Some(directory) = &save_directory.0 else {
+        return;
+    };
+    let path = directory.join(COMPARISON_FILE_NAME);
+    if !path.exists() {
+        return;
+    }
+    match read_lss(&path) {
+        Ok(splits) => comparison_splits.0 = splits,
+        Err(e) => warn!("could not load comparison splits: {e}"),
+    }
+}
+
+/// Imports a `.lss` file dropped onto the window as the comparison for future runs
+fn import_dropped_splits(
+    mut drag_and_drop_events: EventReader<FileDragAndDrop>,
+    save_directory: Res<SaveDirectory>,
+    mut comparison_splits: ResMut<ComparisonSplits>,
+) {
+    for event in drag_and_drop_events.read() {
+        let FileDragAndDrop::DroppedFile { path_buf, .. } = event else {
+            continue;
+        };
+        if path_buf.extension().and_then(|ext| ext.to_str()) != Some("lss") {
+            continue;
+        }
+        match read_lss(path_buf) {
+            Ok(splits) => comparison_splits.0 = splits,
+            Err(e) => {
+                warn!("could not import splits from {}: {e}", path_buf.display());
+                continue;
+            }
+        }
+        if let Some(directory) = &save_directory.0 {
+            let result = fs::create_dir_all(directory)
+                .and_then(|_| fs::copy(path_buf, directory.join(COMPARISON_FILE_NAME)));
+            if let Err(e) = result {
+                warn!("could not keep imported splits: {e}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::level::LevelSplit;
+
+    /// A file in the temp directory that is deleted again when dropped
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str, contents: &str) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "cyclic-tower-splits-{name}-{}.lss",
+                std::process::id()
+            ));
+            fs::write(&path, contents).unwrap();
+            Self(path)
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    fn split(level_name: &str, level_time: f32, run_time: f32) -> LevelSplit {
+        LevelSplit {
+            pack_id: "main".into(),
+            level_iid: level_name.into(),
+            level_name: level_name.into(),
+            level_time,
+            run_time,
+            deaths: 2,
+            cycles: 3,
+        }
+    }
+
+    #[test]
+    fn formats_and_parses_times() {
+        assert_eq!(format_lss_time(125.25), "00:02:05.2500000");
+        assert_eq!(format_lss_time(3725.5), "01:02:05.5000000");
+        assert_eq!(format_lss_time(-1.), "00:00:00.0000000");
+        assert_eq!(parse_lss_time("00:02:05.3100000").unwrap(), 125.31);
+        assert_eq!(parse_lss_time("1:02:05.5").unwrap(), 3725.5);
+        assert_eq!(parse_lss_time(" 42 ").unwrap(), 42.);
+    }
+
+    #[test]
+    fn lss_round_trips() {
+        let run_splits = RunSplits(vec![
+            split("Level 1", 12.5, 12.5),
+            split("<Level \"2\"> & more", 30.25, 42.75),
+        ]);
+        let lss = write_lss(&run_splits, &BestTimes(default()), 2, 3);
+        let file = TempFile::new("round-trip", &lss);
+
+        let splits = read_lss(&file.0).unwrap();
+        assert_eq!(splits.len(), 2);
+        for (read, written) in splits.iter().zip(run_splits.0.iter()) {
+            assert_eq!(read.level_name, written.level_name);
+            assert!((read.run_time - written.run_time).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn malformed_splits_are_errors() {
+        for time in ["", "00:xx:05", "1:2:3:4.5.6"] {
+            assert!(
+                matches!(parse_lss_time(time), Err(SplitsError::InvalidTime(_))),
+                "{time:?} should be invalid"
+            );
+        }
+
+        let missing = std::env::temp_dir().join("cyclic-tower-splits-missing.lss");
+        assert!(matches!(read_lss(&missing), Err(SplitsError::Io(_))));
+
+        let not_xml = TempFile::new("not-xml", "<Run><Segments>");
+        assert!(matches!(read_lss(&not_xml.0), Err(SplitsError::Xml(_))));
+
+        let lss = write_lss(
+            &RunSplits(vec![split("Level 1", 1., 1.)]),
+            &BestTimes(default()),
+            0,
+            0,
+        )
+        .replace("00:00:01.0000000", "one second");
+        let invalid_time = TempFile::new("invalid-time", &lss);
+        assert!(matches!(
+            read_lss(&invalid_time.0),
+            Err(SplitsError::InvalidTime(time)) if time == "one second"
+        ));
+    }
+
+    #[test]
+    fn csv_has_a_row_per_level() {
+        let run_splits = RunSplits(vec![
+            split("Level 1", 12.5, 12.5),
+            split("The \"End\"", 30.25, 42.75),
+        ]);
+        assert_eq!(
+            write_csv(&run_splits),
+            "level,pack,level_time,run_time,deaths,cycles\n\
+             \"Level 1\",main,12.500,12.500,2,3\n\
+             \"The \"\"End\"\"\",main,30.250,42.750,2,3\n"
+        );
+    }
+}
@@ -1,17 +1,66 @@
 use bevy::prelude::*;
 
-pub struct StateManagementPlugin;
+use crate::level::manifest::LevelManifest;
+
+#[derive(Default)]
+pub struct StateManagementPlugin {
+    /// Level to load as soon as the level manifest is available, instead of waiting in the main menu
+    pub starting_level: Option<StartingLevel>,
+}
 
 impl Plugin for StateManagementPlugin {
     fn build(&self, app: &mut App) {
         app.insert_state(LevelLoadingState::MainMenu)
-            .insert_resource(TargetLevel(0));
+            .insert_resource(TargetPack(0))
+            .insert_resource(TargetLevel(0))
+            .add_systems(
+                Update,
+                start_on_level
+                    .run_if(resource_exists::<StartingLevel>)
+                    .run_if(in_state(LevelLoadingState::MainMenu)),
+            );
+
+        if let Some(starting_level) = &self.starting_level {
+            app.insert_resource(starting_level.clone());
+        }
     }
 }
 
+/// Index of the level pack being played, in the order of the level manifest
+#[derive(Resource)]
+pub struct TargetPack(pub usize);
+
+/// Index of the level being played within the target pack
 #[derive(Resource)]
 pub struct TargetLevel(pub usize);
 
+/// Level the game starts on, skipping the main menu
+#[derive(Resource, Clone, Debug)]
+pub struct StartingLevel {
+    /// Id of the pack the level is in, or the first pack if `None`
+    pub pack: Option<String>,
+    pub level: LevelSelector,
+}
+
+/// Picks a level out of a pack
+#[derive(Clone, Debug)]
+pub enum LevelSelector {
+    /// Position in the pack, counting from 1
+    Number(usize),
+    /// Name as listed in the level manifest
+    Name(String),
+}
+
+impl From<&str> for LevelSelector {
+    /// A number if `value` is one, otherwise a name
+    fn from(value: &str) -> Self {
+        match value.parse() {
+            Ok(number) => Self::Number(number),
+            Err(_) => Self::Name(value.into()),
+        }
+    }
+}
+
 #[derive(States, Debug, Clone, PartialEq, Eq, Hash)]
 pub enum LevelLoadingState {
     MainMenu,
@@ -21,4 +70,52 @@ pub enum LevelLoadingState {
     EndScreen,
 }
 
+/// Loads the starting level once the level manifest is available, or quits if the manifest has no
+/// such level
+fn start_on_level(
+    mut commands: Commands,
+    starting_level: Res<StartingLevel>,
+    level_manifest: Res<LevelManifest>,
+    mut target_pack: ResMut<TargetPack>,
+    mut target_level: ResMut<TargetLevel>,
+    mut next_state: ResMut<NextState<LevelLoadingState>>,
+    mut app_exit_events: EventWriter<AppExit>,
+) {
+    if level_manifest.packs.is_empty() {
+        return;
+    }
+    // only ever start on it once, so leaving the level goes back to the main menu
+    commands.remove_resource::<StartingLevel>();
 
+    let Some((pack_index, pack)) =
+        level_manifest
+            .packs
+            .iter()
+            .enumerate()
+            .find(|(_, pack)| match &starting_level.pack {
+                Some(pack_id) => pack.id == *pack_id,
+                None => true,
+            })
+    else {
+        error!("there's no level pack {:?}", starting_level.pack);
+        app_exit_events.send(AppExit::error());
+        return;
+    };
+    let level_index = match &starting_level.level {
+        LevelSelector::Number(number) => number
+            .checked_sub(1)
+            .filter(|index| *index < pack.levels.len()),
+        LevelSelector::Name(name) => pack.levels.iter().position(|level| level.name == *name),
+    };
+    let Some(level_index) = level_index else {
+        error!(
+            "there's no level {:?} in the pack {}",
+            starting_level.level, pack.id
+        );
+        app_exit_events.send(AppExit::error());
+        return;
+    };
+    target_pack.0 = pack_index;
+    target_level.0 = level_index;
+    next_state.set(LevelLoadingState::Loading);
+}
@@ -0,0 +1,95 @@
+mod common;
+
+use bevy::prelude::*;
+
+use common::{TestGame, LANDING_FRAMES};
+use cyclic_tower::player::{
+    abilities::{
+        air_jump_token::AirJumpTokens,
+        dash::{Dash, DASH_KEYCODE},
+        double_jump::DoubleJump,
+    },
+    PlayerState,
+};
+
+/// Frames between jumps, past the jump cooldown and the time the player can still jump after
+/// leaving the ground
+const JUMP_GAP_FRAMES: usize = 15;
+/// Frames into a jump the player is off the ground, where jump tokens are kept
+const AIRBORNE_FRAMES: usize = 5;
+/// Frames from the start of a dash until the player can dash again
+const DASH_COOLDOWN_FRAMES: usize = 25;
+
+#[test]
+fn double_jumps_are_used_before_jump_tokens() {
+    let mut game = TestGame::landed_on("main", "Level 1");
+    game.give_player(DoubleJump {
+        max: 1,
+        remaining: 1,
+    });
+
+    game.tap(KeyCode::ArrowUp).step(AIRBORNE_FRAMES);
+    game.give_player(AirJumpTokens(1));
+    game.step(JUMP_GAP_FRAMES - AIRBORNE_FRAMES);
+    game.tap(KeyCode::ArrowUp).step(1);
+    assert_eq!(game.player::<DoubleJump>().remaining, 0);
+    assert_eq!(game.player::<AirJumpTokens>().0, 1);
+
+    game.step(JUMP_GAP_FRAMES);
+    game.tap(KeyCode::ArrowUp).step(1);
+    assert_eq!(game.player::<AirJumpTokens>().0, 0);
+}
+
+#[test]
+fn landing_refills_double_jumps() {
+    let mut game = TestGame::landed_on("main", "Level 1");
+    game.give_player(DoubleJump {
+        max: 2,
+        remaining: 0,
+    });
+    game.step(1);
+    assert_eq!(game.player::<DoubleJump>().remaining, 2);
+}
+
+#[test]
+fn air_jumps_only_go_up_with_an_ability() {
+    let mut game = TestGame::landed_on("main", "Level 1");
+    game.tap(KeyCode::ArrowUp).step(JUMP_GAP_FRAMES);
+    let before = game.player::<Transform>().translation.y;
+    game.tap(KeyCode::ArrowUp).step(5);
+    let without = game.player::<Transform>().translation.y - before;
+
+    let mut game = TestGame::landed_on("main", "Level 1");
+    game.tap(KeyCode::ArrowUp).step(AIRBORNE_FRAMES);
+    game.give_player(AirJumpTokens(1));
+    game.step(JUMP_GAP_FRAMES - AIRBORNE_FRAMES);
+    let before = game.player::<Transform>().translation.y;
+    game.tap(KeyCode::ArrowUp).step(5);
+    let with = game.player::<Transform>().translation.y - before;
+
+    assert!(with > without + 5.);
+}
+
+#[test]
+fn dashes_are_used_up_until_landing() {
+    let mut game = TestGame::landed_on("main", "Level 1");
+    game.give_player(Dash::default());
+    // a held jump stays in the air long enough to dash twice
+    game.press(KeyCode::ArrowUp).step(JUMP_GAP_FRAMES);
+
+    game.tap(DASH_KEYCODE).step(1);
+    let before = game.player::<Transform>().translation;
+    game.step(4);
+    assert_eq!(*game.player::<PlayerState>(), PlayerState::Dashing);
+    assert!(!game.player::<Dash>().ready);
+    let moved = game.player::<Transform>().translation - before;
+    assert!(moved.x.abs() > 10. && moved.y.abs() < 1.);
+
+    // past the cooldown but still in the air
+    game.step(DASH_COOLDOWN_FRAMES - 5);
+    game.tap(DASH_KEYCODE).step(1);
+    assert!(!game.player::<Dash>().dashing());
+
+    game.release(KeyCode::ArrowUp).step(LANDING_FRAMES);
+    assert!(game.player::<Dash>().ready);
+}
@@ -0,0 +1,139 @@
+use bevy::{math::UVec2, prelude::*, state::app::StatesPlugin};
+use cyclic_tower::{
+    args::ArgsError,
+    level::manifest::{LevelManifest, LevelManifestEntry, LevelPack},
+    state::{LevelSelector, StartingLevel, StateManagementPlugin},
+    GamePlugin,
+};
+
+fn parse(args: &[&str]) -> Result<GamePlugin, ArgsError> {
+    GamePlugin::from_args(
+        std::iter::once("cyclic-tower")
+            .chain(args.iter().copied())
+            .map(String::from),
+    )
+}
+
+#[test]
+fn no_options_start_in_the_main_menu() {
+    let game = parse(&[]).unwrap();
+    assert!(game.starting_level.is_none());
+    assert!(game.window_size.is_none());
+    assert!(!game.muted);
+}
+
+#[test]
+fn level_is_a_number_or_a_name() {
+    let game = parse(&["--level", "3", "--pack=main"]).unwrap();
+    let Some(StartingLevel {
+        pack,
+        level: LevelSelector::Number(3),
+    }) = game.starting_level
+    else {
+        panic!("expected the third level, got {:?}", game.starting_level);
+    };
+    assert_eq!(pack.as_deref(), Some("main"));
+
+    let game = parse(&["--level=Level 1"]).unwrap();
+    let Some(StartingLevel {
+        pack: None,
+        level: LevelSelector::Name(name),
+    }) = game.starting_level
+    else {
+        panic!("expected a level name, got {:?}", game.starting_level);
+    };
+    assert_eq!(name, "Level 1");
+}
+
+#[test]
+fn flags_and_window_size() {
+    let game = parse(&[
+        "--windowed",
+        "640x360",
+        "--mute",
+        "--vsync",
+        "--debug-physics",
+    ])
+    .unwrap();
+    assert_eq!(game.window_size, Some(UVec2::new(640, 360)));
+    assert!(game.muted && game.vsync && game.debug_physics);
+}
+
+#[test]
+fn other_plugins_options_are_left_alone() {
+    let game = parse(&["--replay=last.replay.ron", "--autosplitter"]).unwrap();
+    assert!(game.starting_level.is_none());
+    assert!(parse(&["--autosplitter=127.0.0.1:4000"]).is_ok());
+}
+
+#[test]
+fn invalid_options_are_errors() {
+    assert!(matches!(
+        parse(&["--windowed", "big"]),
+        Err(ArgsError::WindowSize(_))
+    ));
+    assert!(matches!(
+        parse(&["--log-level", "loud"]),
+        Err(ArgsError::LogLevel(_))
+    ));
+    assert!(matches!(
+        parse(&["--level"]),
+        Err(ArgsError::MissingValue(_))
+    ));
+    assert!(matches!(
+        parse(&["--mute=yes"]),
+        Err(ArgsError::UnexpectedValue(_))
+    ));
+    assert!(matches!(
+        parse(&["--fullscreen"]),
+        Err(ArgsError::UnknownOption(_))
+    ));
+    assert!(matches!(
+        parse(&["--replay", "last.replay.ron"]),
+        Err(ArgsError::UnknownOption(_))
+    ));
+}
+
+/// Runs a frame of the game's states with a manifest of one pack, starting on `level`
+fn start_on(pack: Option<&str>, level: LevelSelector) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        StatesPlugin,
+        StateManagementPlugin {
+            starting_level: Some(StartingLevel {
+                pack: pack.map(String::from),
+                level,
+            }),
+        },
+    ))
+    .insert_resource(LevelManifest {
+        packs: vec![LevelPack {
+            id: "main".into(),
+            name: "Dungeon".into(),
+            project: "level.ldtk".into(),
+            levels: vec![LevelManifestEntry {
+                iid: "584033f0-25d0-11ef-8b42-1596277d2df3".into(),
+                name: "Level 1".into(),
+            }],
+        }],
+    });
+    app.update();
+    app
+}
+
+#[test]
+fn starting_levels_missing_from_the_manifest_quit() {
+    assert!(start_on(Some("main"), LevelSelector::Number(1))
+        .should_exit()
+        .is_none());
+    assert!(start_on(None, LevelSelector::Number(2))
+        .should_exit()
+        .is_some_and(|exit| exit.is_error()));
+    assert!(start_on(None, LevelSelector::Name("Level 9".into()))
+        .should_exit()
+        .is_some_and(|exit| exit.is_error()));
+    assert!(start_on(Some("bonus"), LevelSelector::Number(1))
+        .should_exit()
+        .is_some_and(|exit| exit.is_error()));
+}
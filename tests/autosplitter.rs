@@ -0,0 +1,137 @@
+use std::{
+    io::{self, Read},
+    net::{TcpListener, TcpStream},
+    time::Duration,
+};
+
+use bevy::{prelude::*, state::app::StatesPlugin};
+use cyclic_tower::{
+    args::ArgsError,
+    autosplitter::AutosplitterPlugin,
+    menus::{DeathCount, SpeedrunTimer},
+    player::LevelCompletedEvent,
+    state::LevelLoadingState,
+};
+
+/// Frames to wait for the game to connect or for its commands to arrive
+const MAX_FRAMES: usize = 200;
+
+/// The game's autosplitter connected to a mock LiveSplit Server
+struct MockLiveSplit {
+    app: App,
+    stream: TcpStream,
+    received: String,
+}
+
+impl MockLiveSplit {
+    fn connect() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.set_nonblocking(true).unwrap();
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            StatesPlugin,
+            AutosplitterPlugin {
+                address: Some(listener.local_addr().unwrap()),
+            },
+        ))
+        .insert_state(LevelLoadingState::MainMenu)
+        .insert_resource(SpeedrunTimer(default()))
+        .insert_resource(DeathCount(0));
+
+        for _ in 0..MAX_FRAMES {
+            app.update();
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    stream.set_nonblocking(true).unwrap();
+                    return Self {
+                        app,
+                        stream,
+                        received: String::new(),
+                    };
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    std::thread::sleep(Duration::from_millis(5))
+                }
+                Err(e) => panic!("mock LiveSplit Server failed: {e}"),
+            }
+        }
+        panic!("the game never connected");
+    }
+
+    fn set_state(&mut self, state: LevelLoadingState) {
+        self.app
+            .world_mut()
+            .resource_mut::<NextState<LevelLoadingState>>()
+            .set(state);
+    }
+
+    /// Runs frames until the game sent `line`, and returns everything received up to it
+    fn expect_line(&mut self, line: &str) -> String {
+        for _ in 0..MAX_FRAMES {
+            if let Some(end) = self
+                .received
+                .split_inclusive("\r\n")
+                .position(|received| received == format!("{line}\r\n"))
+            {
+                let lines: Vec<&str> = self.received.split_inclusive("\r\n").collect();
+                let before = lines[..=end].concat();
+                self.received = lines[end + 1..].concat();
+                return before;
+            }
+            self.app.update();
+            let mut buffer = [0; 1024];
+            match self.stream.read(&mut buffer) {
+                Ok(read) => self
+                    .received
+                    .push_str(std::str::from_utf8(&buffer[..read]).unwrap()),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    std::thread::sleep(Duration::from_millis(5))
+                }
+                Err(e) => panic!("mock LiveSplit Server failed: {e}"),
+            }
+        }
+        panic!("never received {line:?}, got {:?}", self.received);
+    }
+}
+
+#[test]
+fn starting_finishing_and_leaving_a_run_are_sent_to_livesplit() {
+    let mut livesplit = MockLiveSplit::connect();
+
+    livesplit.set_state(LevelLoadingState::Loading);
+    livesplit.expect_line("starttimer");
+
+    livesplit.app.world_mut().send_event(LevelCompletedEvent);
+    let split = livesplit.expect_line("split");
+    assert!(split.contains("setgametime "));
+
+    livesplit.set_state(LevelLoadingState::Loaded);
+    livesplit.app.update();
+    livesplit.set_state(LevelLoadingState::MainMenu);
+    livesplit.expect_line("reset");
+}
+
+#[test]
+fn autosplitter_flag_takes_an_optional_address() {
+    let args = |args: &[&str]| {
+        AutosplitterPlugin::from_args(
+            std::iter::once("cyclic-tower")
+                .chain(args.iter().copied())
+                .map(String::from),
+        )
+    };
+    assert!(args(&[]).unwrap().address.is_none());
+    assert_eq!(
+        args(&["--autosplitter"]).unwrap().address,
+        Some("127.0.0.1:16834".parse().unwrap())
+    );
+    assert_eq!(
+        args(&["--autosplitter=127.0.0.1:4000"]).unwrap().address,
+        Some("127.0.0.1:4000".parse().unwrap())
+    );
+    assert!(matches!(
+        args(&["--autosplitter=livesplit"]),
+        Err(ArgsError::AutosplitterAddress(_))
+    ));
+}
@@ -0,0 +1,222 @@
+//! Runs the game headless, so tests can play levels with scripted input on a machine without a GPU
+
+// every test binary includes this, but only uses some of it
+#![allow(dead_code)]
+
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use bevy::{
+    input::{
+        keyboard::{Key, KeyboardInput, NativeKey},
+        ButtonState,
+    },
+    prelude::*,
+    time::TimeUpdateStrategy,
+};
+use bevy_ecs_ldtk::{
+    ldtk::{EntityInstance, LayerInstance},
+    prelude::*,
+};
+use cyclic_tower::{
+    physics::FIXED_TIMESTEP_HZ,
+    player::{PlayerColliderMarker, PlayerMarker},
+    state::{LevelLoadingState, StartingLevel},
+    GamePlugin,
+};
+
+/// How long to wait for assets to load before failing a test
+const LOAD_TIMEOUT: Duration = Duration::from_secs(30);
+/// Frames for the player to fall onto the floor they spawn above
+pub const LANDING_FRAMES: usize = 60;
+/// Frames for the screen to fade out after dying or leaving a level
+pub const FADE_FRAMES: usize = 60;
+
+/// The game running headless, where every frame is exactly one gameplay tick
+pub struct TestGame {
+    app: App,
+}
+
+impl TestGame {
+    /// Starts the game on the level called `level_name` in the pack with id `pack_id`, once the
+    /// player has spawned in it
+    pub fn load_level(pack_id: &str, level_name: &str) -> Self {
+        let mut game = Self::new(Some(StartingLevel {
+            pack: Some(pack_id.into()),
+            level: level_name.into(),
+        }));
+        game.wait_for_player();
+        game
+    }
+
+    /// Starts the first level straight from the main menu on the first frame, likely before the
+    /// level manifest has loaded, once the player has spawned in it
+    pub fn start_immediately() -> Self {
+        let mut game = Self::new(None);
+        game.app
+            .world_mut()
+            .resource_mut::<NextState<LevelLoadingState>>()
+            .set(LevelLoadingState::Loading);
+        game.wait_for_player();
+        game
+    }
+
+    fn new(starting_level: Option<StartingLevel>) -> Self {
+        let mut app = App::new();
+        app.add_plugins(GamePlugin {
+            headless: true,
+            starting_level,
+            ..default()
+        })
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            1. / FIXED_TIMESTEP_HZ,
+        )));
+        // done by `App::run` otherwise, and some plugins only register their asset loaders here
+        app.finish();
+        app.cleanup();
+        Self { app }
+    }
+
+    fn wait_for_player(&mut self) {
+        self.wait_until("the player to spawn", |world| {
+            world
+                .query_filtered::<(), With<PlayerColliderMarker>>()
+                .iter(world)
+                .next()
+                .is_some()
+        });
+    }
+
+    /// Starts the game like [`TestGame::load_level`], once the player has landed on the floor they
+    /// spawn above
+    pub fn landed_on(pack_id: &str, level_name: &str) -> Self {
+        let mut game = Self::load_level(pack_id, level_name);
+        game.step(LANDING_FRAMES);
+        game
+    }
+
+    /// Runs frames until `done` returns true, giving assets time to load in between
+    fn wait_until(&mut self, what: &str, mut done: impl FnMut(&mut World) -> bool) {
+        let start = Instant::now();
+        while !done(self.app.world_mut()) {
+            assert!(
+                start.elapsed() < LOAD_TIMEOUT,
+                "timed out waiting for {what}"
+            );
+            self.app.update();
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    /// Runs `frames` frames
+    pub fn step(&mut self, frames: usize) -> &mut Self {
+        for _ in 0..frames {
+            self.app.update();
+        }
+        self
+    }
+
+    /// Holds `key` down from the next frame on
+    pub fn press(&mut self, key: KeyCode) -> &mut Self {
+        self.send_key(key, ButtonState::Pressed)
+    }
+
+    /// Lets go of `key` from the next frame on
+    pub fn release(&mut self, key: KeyCode) -> &mut Self {
+        self.send_key(key, ButtonState::Released)
+    }
+
+    /// Presses `key` for a single frame
+    pub fn tap(&mut self, key: KeyCode) -> &mut Self {
+        self.press(key).step(1).release(key)
+    }
+
+    fn send_key(&mut self, key_code: KeyCode, state: ButtonState) -> &mut Self {
+        self.app.world_mut().send_event(KeyboardInput {
+            key_code,
+            logical_key: Key::Unidentified(NativeKey::Unidentified),
+            state,
+            window: Entity::PLACEHOLDER,
+        });
+        self
+    }
+
+    pub fn resource<R: Resource>(&self) -> &R {
+        self.app.world().resource::<R>()
+    }
+
+    pub fn state(&self) -> &LevelLoadingState {
+        self.resource::<State<LevelLoadingState>>().get()
+    }
+
+    /// Spawns `bundle` at `translation`, for tests that need an entity somewhere in the level
+    pub fn spawn_at(&mut self, bundle: impl Bundle, translation: Vec3) -> Entity {
+        self.app
+            .world_mut()
+            .spawn(bundle)
+            .insert(Transform::from_translation(translation))
+            .id()
+    }
+
+    /// Spawns `entity_instance` into the level's entity layer as LDtk would, with the bundle `B`
+    /// registered for it
+    pub fn spawn_ldtk_entity<B: LdtkEntity + Bundle>(
+        &mut self,
+        entity_instance: &EntityInstance,
+    ) -> Entity {
+        let world = self.app.world_mut();
+        let layer = world
+            .query::<(Entity, &LayerMetadata)>()
+            .iter(world)
+            .find(|(_, layer)| layer.identifier == "Entities")
+            .map(|(layer, _)| layer)
+            .expect("the level should have an entity layer");
+        let bundle = world.resource_scope(|world, mut texture_atlases| {
+            B::bundle_entity(
+                entity_instance,
+                &LayerInstance::default(),
+                None,
+                None,
+                world.resource::<AssetServer>(),
+                &mut texture_atlases,
+            )
+        });
+        world.spawn(bundle).set_parent(layer).id()
+    }
+
+    /// The `T` component of `entity`, if it still has one
+    pub fn get<T: Component>(&self, entity: Entity) -> Option<&T> {
+        self.app.world().get::<T>(entity)
+    }
+
+    /// Gives the player `bundle`, like an ability they'd otherwise have to pick up
+    pub fn give_player(&mut self, bundle: impl Bundle) -> &mut Self {
+        let world = self.app.world_mut();
+        let player = world
+            .query_filtered::<Entity, With<PlayerMarker>>()
+            .single(world);
+        world.entity_mut(player).insert(bundle);
+        self
+    }
+
+    /// Whether the player has a `T` component
+    pub fn player_has<T: Component>(&mut self) -> bool {
+        let world = self.app.world_mut();
+        world
+            .query_filtered::<(), (With<T>, With<PlayerMarker>)>()
+            .iter(world)
+            .next()
+            .is_some()
+    }
+
+    /// The player's `T` component
+    pub fn player<T: Component>(&mut self) -> &T {
+        let mut query = self
+            .app
+            .world_mut()
+            .query_filtered::<&T, With<PlayerMarker>>();
+        query.single(self.app.world())
+    }
+}
@@ -0,0 +1,13 @@
+mod common;
+
+use common::TestGame;
+use cyclic_tower::echo::{EchoMode, LevelEcho};
+
+#[test]
+fn levels_pick_their_echo_in_ldtk() {
+    let game = TestGame::load_level("bonus", "Bonus 1");
+    assert_eq!(game.resource::<LevelEcho>().0, Some(EchoMode::Helper));
+
+    let game = TestGame::load_level("main", "Level 1");
+    assert_eq!(game.resource::<LevelEcho>().0, None);
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use bevy_ecs_ldtk::ldtk::{EntityInstance, FieldInstance, FieldValue};
+use common::TestGame;
+use cyclic_tower::entities::{
+    fields::LevelFieldErrors,
+    lever::LeverBundle,
+    platform::{PlatformBundle, PlatformInfo},
+};
+
+fn entity_instance(identifier: &str, fields: Vec<(&str, FieldValue)>) -> EntityInstance {
+    EntityInstance {
+        identifier: identifier.into(),
+        field_instances: fields
+            .into_iter()
+            .map(|(identifier, value)| FieldInstance {
+                identifier: identifier.into(),
+                tile: None,
+                field_instance_type: String::new(),
+                value,
+                def_uid: 0,
+                real_editor_values: Vec::new(),
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn broken_entities_spawn_with_fallbacks() {
+    let mut game = TestGame::landed_on("main", "Level 1");
+    game.spawn_ldtk_entity::<LeverBundle>(&entity_instance(
+        "Lever",
+        vec![("lever_id", FieldValue::Int(Some(9)))],
+    ));
+    let platform = game.spawn_ldtk_entity::<PlatformBundle>(&entity_instance(
+        "LeverPlatform",
+        vec![("platform_id", FieldValue::String(None))],
+    ));
+    game.step(10);
+
+    let platform_info = game.get::<PlatformInfo>(platform).unwrap();
+    assert_eq!((platform_info.id, platform_info.active), (0, true));
+    assert_eq!(
+        game.resource::<LevelFieldErrors>().0,
+        [
+            "Level_0: Lever at [0, 0]: lever_id is 9, expected 1 to 4",
+            "Level_0: LeverPlatform at [0, 0]: found platform_id field, but its type is not correct",
+            "Level_0: LeverPlatform at [0, 0]: could not find init_state field",
+        ]
+    );
+}
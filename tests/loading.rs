@@ -0,0 +1,11 @@
+mod common;
+
+use common::TestGame;
+use cyclic_tower::state::LevelLoadingState;
+
+#[test]
+fn starting_before_the_manifest_loads_still_loads_the_level() {
+    let mut game = TestGame::start_immediately();
+    game.step(60);
+    assert_eq!(*game.state(), LevelLoadingState::Loaded);
+}
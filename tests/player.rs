@@ -0,0 +1,101 @@
+mod common;
+
+use bevy::prelude::*;
+
+use common::{TestGame, FADE_FRAMES};
+use cyclic_tower::{
+    menus::DeathCount,
+    player::{
+        abilities::{air_jump_token::AirJumpTokens, double_jump::DoubleJump, wall_jump::WallJump},
+        PlayerCheckpoint, PlayerInventory, PlayerStatus,
+    },
+    state::LevelLoadingState,
+};
+
+/// Frames a full jump takes to land
+const JUMP_FRAMES: usize = 70;
+
+#[test]
+fn player_spawns_alive_with_an_empty_inventory() {
+    let mut game = TestGame::landed_on("main", "Level 1");
+    assert_eq!(*game.state(), LevelLoadingState::Loaded);
+    let player_status = game.player::<PlayerStatus>();
+    assert!(!player_status.dead);
+    assert!(!player_status.level_finished);
+    assert_eq!(game.player::<PlayerInventory>().num_keys, 0);
+    assert!(!game.player_has::<DoubleJump>());
+    assert!(!game.player_has::<WallJump>());
+    assert!(!game.player_has::<AirJumpTokens>());
+}
+
+#[test]
+fn holding_right_moves_the_player_right() {
+    let mut game = TestGame::landed_on("main", "Level 1");
+    let start = game.player::<Transform>().translation;
+    game.press(KeyCode::ArrowRight).step(20);
+    assert!(game.player::<Transform>().translation.x > start.x + 10.);
+}
+
+#[test]
+fn jumping_moves_the_player_up() {
+    let mut game = TestGame::landed_on("main", "Level 1");
+    let start = game.player::<Transform>().translation;
+    game.press(KeyCode::ArrowUp).step(10);
+    assert!(game.player::<Transform>().translation.y > start.y + 10.);
+}
+
+/// How high above where they started the player gets within `frames` frames
+fn peak_height(game: &mut TestGame, frames: usize) -> f32 {
+    let start = game.player::<Transform>().translation.y;
+    let mut peak = start;
+    for _ in 0..frames {
+        peak = peak.max(game.step(1).player::<Transform>().translation.y);
+    }
+    peak - start
+}
+
+#[test]
+fn letting_go_of_jump_early_makes_a_lower_jump() {
+    let mut game = TestGame::landed_on("main", "Level 1");
+    game.tap(KeyCode::ArrowUp);
+    let hop = peak_height(&mut game, JUMP_FRAMES);
+
+    let mut game = TestGame::landed_on("main", "Level 1");
+    game.press(KeyCode::ArrowUp);
+    let jump = peak_height(&mut game, JUMP_FRAMES);
+
+    assert!(hop > 0.);
+    assert!(jump > hop * 2.);
+}
+
+#[test]
+fn restarting_respawns_the_player_at_their_checkpoint() {
+    let mut game = TestGame::landed_on("main", "Level 1");
+    game.press(KeyCode::ArrowRight)
+        .step(20)
+        .release(KeyCode::ArrowRight);
+    game.tap(KeyCode::KeyR);
+    assert!(game.player::<PlayerStatus>().dead);
+
+    game.step(FADE_FRAMES);
+    assert!(!game.player::<PlayerStatus>().dead);
+    assert_eq!(game.resource::<DeathCount>().0, 1);
+    let checkpoint = game.player::<PlayerCheckpoint>().transform;
+    assert!(
+        game.player::<Transform>()
+            .translation
+            .xy()
+            .distance(checkpoint)
+            < 16.
+    );
+}
+
+#[test]
+fn leaving_the_level_returns_to_the_main_menu() {
+    let mut game = TestGame::landed_on("main", "Level 1");
+    game.tap(KeyCode::Escape);
+    assert!(game.player::<PlayerStatus>().exiting);
+
+    game.step(FADE_FRAMES);
+    assert_eq!(*game.state(), LevelLoadingState::MainMenu);
+}
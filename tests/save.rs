@@ -0,0 +1,131 @@
+use std::{collections::HashMap, fs, path::PathBuf};
+
+use bevy::{prelude::*, state::app::StatesPlugin};
+use cyclic_tower::{
+    level::{BestTimes, LastAccessibleLevel},
+    menus::{CycleCount, DeathCount, SpeedrunTimer},
+    save::{
+        read_save, write_save_file, RunProgress, SaveData, SaveError, SaveManagementPlugin,
+        SaveSlot, SaveSlots, SAVE_VERSION,
+    },
+    state::LevelLoadingState,
+};
+
+/// An empty directory for one test's save files, deleted again when dropped
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let path =
+            std::env::temp_dir().join(format!("cyclic-tower-test-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn example_save() -> SaveData {
+    SaveData {
+        last_accessible_level: HashMap::from([("main".to_string(), 3)]),
+        best_times: HashMap::from([(
+            "main".to_string(),
+            HashMap::from([("Level_0".to_string(), 12.5)]),
+        )]),
+        run: RunProgress {
+            death_count: 7,
+            cycle_count: 2,
+            speedrun_time: 95.25,
+        },
+        ..default()
+    }
+}
+
+#[test]
+fn save_files_round_trip() {
+    let dir = TempDir::new("round-trip");
+    assert_eq!(read_save(&dir.0, 1).unwrap(), None);
+
+    write_save_file(&dir.0, 1, &example_save()).unwrap();
+    assert_eq!(read_save(&dir.0, 1).unwrap(), Some(example_save()));
+}
+
+#[test]
+fn unsupported_versions_are_errors() {
+    let dir = TempDir::new("versions");
+    fs::write(
+        dir.0.join("save_1.ron"),
+        format!("(version: {})", SAVE_VERSION + 1),
+    )
+    .unwrap();
+    fs::write(dir.0.join("save_2.ron"), "(version: 0)").unwrap();
+
+    assert!(matches!(
+        read_save(&dir.0, 0),
+        Err(SaveError::NewerVersion(version)) if version == SAVE_VERSION + 1
+    ));
+    assert!(matches!(
+        read_save(&dir.0, 1),
+        Err(SaveError::UnknownVersion(0))
+    ));
+}
+
+/// The save plugin with the progress resources it saves, sitting in the main menu
+fn save_app(dir: &TempDir) -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, StatesPlugin))
+        .insert_resource(LastAccessibleLevel(HashMap::new()))
+        .insert_resource(BestTimes(HashMap::new()))
+        .insert_resource(SpeedrunTimer(default()))
+        .insert_resource(DeathCount(0))
+        .insert_resource(CycleCount(0))
+        .insert_state(LevelLoadingState::MainMenu)
+        .add_plugins(SaveManagementPlugin {
+            directory: Some(dir.0.clone()),
+        });
+    app.finish();
+    app.update();
+    app
+}
+
+fn set_state(app: &mut App, state: LevelLoadingState) {
+    app.world_mut()
+        .resource_mut::<NextState<LevelLoadingState>>()
+        .set(state);
+    app.update();
+}
+
+#[test]
+fn only_playing_writes_the_save() {
+    let dir = TempDir::new("menus");
+    let mut app = save_app(&dir);
+    set_state(&mut app, LevelLoadingState::LevelSelect);
+    set_state(&mut app, LevelLoadingState::MainMenu);
+    assert_eq!(read_save(&dir.0, 0).unwrap(), None);
+
+    set_state(&mut app, LevelLoadingState::Loading);
+    assert!(read_save(&dir.0, 0).unwrap().is_some());
+}
+
+#[test]
+fn unreadable_slots_are_not_saved_over() {
+    let dir = TempDir::new("unreadable");
+    fs::write(dir.0.join("save_1.ron"), "not a save file").unwrap();
+
+    let mut app = save_app(&dir);
+    set_state(&mut app, LevelLoadingState::Loading);
+
+    assert!(matches!(
+        app.world().resource::<SaveSlots>().0[0],
+        SaveSlot::Unreadable(_)
+    ));
+    assert_eq!(
+        fs::read_to_string(dir.0.join("save_1.ron")).unwrap(),
+        "not a save file"
+    );
+}
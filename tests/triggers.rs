@@ -0,0 +1,46 @@
+mod common;
+
+use bevy::prelude::*;
+
+use common::TestGame;
+use cyclic_tower::{
+    entities::{jump_token::JumpTokenBundle, key::KeyBundle},
+    player::{abilities::air_jump_token::AirJumpTokens, PlayerInventory},
+};
+
+/// Frames for a spawned trigger to get its sensor and be touched
+const TOUCH_FRAMES: usize = 3;
+/// Frames into a jump the player is well off the ground
+const JUMP_FRAMES: usize = 5;
+/// Frames for a jump token to come back after being picked up
+const JUMP_TOKEN_RESPAWN_FRAMES: usize = 5 * 60;
+
+fn spawn_on_player(game: &mut TestGame, bundle: impl Bundle) -> Entity {
+    let translation = game.player::<Transform>().translation;
+    game.spawn_at(bundle, translation)
+}
+
+#[test]
+fn touching_a_key_picks_it_up() {
+    let mut game = TestGame::landed_on("main", "Level 1");
+    let key = spawn_on_player(&mut game, KeyBundle::default());
+    game.step(TOUCH_FRAMES);
+
+    assert_eq!(game.player::<PlayerInventory>().num_keys, 1);
+    assert!(game.get::<Transform>(key).is_none());
+}
+
+#[test]
+fn jump_tokens_respawn_after_being_picked_up() {
+    let mut game = TestGame::landed_on("main", "Level 1");
+    // air jumps are only kept off the ground
+    game.tap(KeyCode::ArrowUp).step(JUMP_FRAMES);
+    let token = spawn_on_player(&mut game, JumpTokenBundle::default());
+    game.step(TOUCH_FRAMES);
+
+    assert_eq!(game.player::<AirJumpTokens>().0, 1);
+    assert_eq!(game.get::<Visibility>(token), Some(&Visibility::Hidden));
+
+    game.step(JUMP_TOKEN_RESPAWN_FRAMES);
+    assert_eq!(game.get::<Visibility>(token), Some(&Visibility::Inherited));
+}